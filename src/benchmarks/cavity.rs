@@ -0,0 +1,185 @@
+use crate::scenes::cavity::LidDrivenCavityScene;
+
+/// Reynolds numbers with reference data.
+pub const REYNOLDS_NUMBERS: [f32; 3] = [100.0, 400.0, 1000.0];
+
+/// Accepted L2 error against Ghia et al., in units of the lid speed, on grids of at least the
+/// given number of cells per side. Ghia's own 129 x 129 profiles differ from spectral solutions
+/// (Botella & Peyret 1998) by up to about 1% of the lid speed, which is as close as a 128-cell
+/// grid can be asked to come. The semi-Lagrangian advection is first order, so the allowance
+/// doubles each time the cells do.
+///
+/// The errors this solver reaches at steady state are
+///
+/// | Re   | 32     | 64     | 128    |
+/// |------|--------|--------|--------|
+/// | 100  | 0.0105 | 0.0052 | 0.0033 |
+/// | 400  | 0.0985 | 0.0581 | 0.0393 |
+/// | 1000 | 0.1916 | 0.1132 | 0.0639 |
+///
+/// At Re = 400 and 1000 the numerical viscosity of the advection is comparable to the physical
+/// one and weakens the main vortex, so those cases are under-resolved on all of these grids and
+/// fail.
+pub const TOLERANCES: [(usize, f32); 3] = [(32, 0.04), (64, 0.02), (128, 0.01)];
+
+/// Accepted L2 error on a grid of `resolution` cells per side: that of the finest entry of
+/// `TOLERANCES` not finer than it, or `None` on grids too coarse to compare with Ghia at all.
+pub fn tolerance(resolution: usize) -> Option<f32> {
+    TOLERANCES.iter().rev().find(|&&(cells, _)| cells <= resolution).map(|&(_, tolerance)| tolerance)
+}
+
+/// Upper limit on the simulated time, in lid transit times.
+const MAX_CONVECTIVE_TIMES: usize = 200;
+/// Relative velocity change per unit time below which the flow counts as steady.
+const STEADY_TOLERANCE: f32 = 1e-5;
+
+/// Ghia, Ghia & Shin (1982), table I: `u` along the vertical line through the cavity center.
+const GHIA_Y: [f32; 17] = [
+    1.0000, 0.9766, 0.9688, 0.9609, 0.9531, 0.8516, 0.7344, 0.6172, 0.5000, 0.4531, 0.2813, 0.1719, 0.1016, 0.0703, 0.0625, 0.0547, 0.0000,
+];
+const GHIA_U: [[f32; 17]; 3] = [
+    [
+        1.00000, 0.84123, 0.78871, 0.73722, 0.68717, 0.23151, 0.00332, -0.13641, -0.20581, -0.21090, -0.15662, -0.10150, -0.06434, -0.04775, -0.04192,
+        -0.03717, 0.00000,
+    ],
+    [
+        1.00000, 0.75837, 0.68439, 0.61756, 0.55892, 0.29093, 0.16256, 0.02135, -0.11477, -0.17119, -0.32726, -0.24299, -0.14612, -0.10338, -0.09266,
+        -0.08186, 0.00000,
+    ],
+    [
+        1.00000, 0.65928, 0.57492, 0.51117, 0.46604, 0.33304, 0.18719, 0.05702, -0.06080, -0.10648, -0.27805, -0.38289, -0.29730, -0.22220, -0.20196,
+        -0.18109, 0.00000,
+    ],
+];
+
+/// Ghia, Ghia & Shin (1982), table II: `v` along the horizontal line through the cavity center.
+const GHIA_X: [f32; 17] = [
+    1.0000, 0.9688, 0.9609, 0.9531, 0.9453, 0.9063, 0.8594, 0.8047, 0.5000, 0.2344, 0.2266, 0.1563, 0.0938, 0.0781, 0.0703, 0.0625, 0.0000,
+];
+const GHIA_V: [[f32; 17]; 3] = [
+    [
+        0.00000, -0.05906, -0.07391, -0.08864, -0.10313, -0.16914, -0.22445, -0.24533, 0.05454, 0.17527, 0.17507, 0.16077, 0.12317, 0.10890, 0.10091,
+        0.09233, 0.00000,
+    ],
+    [
+        0.00000, -0.12146, -0.15663, -0.19254, -0.22847, -0.23827, -0.44993, -0.38598, 0.05186, 0.30174, 0.30203, 0.28124, 0.22965, 0.20920, 0.19713,
+        0.18360, 0.00000,
+    ],
+    [
+        0.00000, -0.21388, -0.27669, -0.33714, -0.39188, -0.51550, -0.42665, -0.31966, 0.02526, 0.32235, 0.33075, 0.37095, 0.32627, 0.30353, 0.29012,
+        0.27485, 0.00000,
+    ],
+];
+
+/// Error of a sampled centerline profile against the reference values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfileError {
+    /// Root mean square difference.
+    pub l2: f32,
+    /// Largest absolute difference.
+    pub max: f32,
+}
+
+impl ProfileError {
+    fn new(simulated: &[f32], reference: &[f32]) -> Self {
+        let differences = simulated.iter().zip(reference).map(|(a, b)| (a - b).abs());
+        let sum_squared: f32 = differences.clone().map(|d| d * d).sum();
+        Self {
+            l2: (sum_squared / reference.len() as f32).sqrt(),
+            max: differences.fold(0.0, f32::max),
+        }
+    }
+}
+
+/// Runs the cavity to steady state at each Reynolds number and compares the centerline
+/// velocities with Ghia et al. Returns whether every L2 error is within `tolerance`, or
+/// within `TOLERANCES` at `resolution` when no tolerance is given.
+pub fn run(reynolds_numbers: &[f32], resolution: usize, tolerance: Option<f32>) -> bool {
+    let Some(tolerance) = tolerance.or_else(|| self::tolerance(resolution)) else {
+        println!("No tolerance below {} cells per side; pass --tolerance to compare anyway", TOLERANCES[0].0);
+        return false;
+    };
+
+    let mut passed = true;
+    let mut errors = Vec::new();
+    for &reynolds in reynolds_numbers {
+        let Some(case) = REYNOLDS_NUMBERS.iter().position(|&re| re == reynolds) else {
+            println!("No reference data at Re = {reynolds}; available: {REYNOLDS_NUMBERS:?}");
+            passed = false;
+            continue;
+        };
+        let [u_error, v_error] = run_case(case, resolution);
+        passed &= u_error.l2 <= tolerance && v_error.l2 <= tolerance;
+        errors.push((reynolds, u_error, v_error));
+    }
+
+    println!("L2 errors on {resolution}x{resolution} cells, tolerance {tolerance:.4}");
+    println!("  {:>6} {:>8} {:>8}", "Re", "u", "v");
+    for (reynolds, u_error, v_error) in errors {
+        let verdict = if u_error.l2 <= tolerance && v_error.l2 <= tolerance { "" } else { "  over" };
+        println!("  {reynolds:>6} {:>8.4} {:>8.4}{verdict}", u_error.l2, v_error.l2);
+    }
+    println!("{}", if passed { "PASSED" } else { "FAILED" });
+    passed
+}
+
+/// Runs case `case` of `REYNOLDS_NUMBERS` to steady state, prints the centerline profiles
+/// and returns the errors of `u` and `v`.
+fn run_case(case: usize, resolution: usize) -> [ProfileError; 2] {
+    let mut scene = LidDrivenCavityScene::new(REYNOLDS_NUMBERS[case], resolution);
+    let convective_time = scene.steps_per_convective_time();
+
+    println!("Lid-driven cavity at Re = {}, {resolution}x{resolution} cells", scene.reynolds);
+    let mut change = f32::INFINITY;
+    for step in 1..=MAX_CONVECTIVE_TIMES * convective_time {
+        change = scene.advance();
+        if change < STEADY_TOLERANCE {
+            break;
+        }
+        if step % (10 * convective_time) == 0 {
+            println!("  t = {:>4} L/U, change {change:.2e}", step / convective_time);
+        }
+    }
+    if change >= STEADY_TOLERANCE {
+        println!("  not fully steady after {MAX_CONVECTIVE_TIMES} L/U (change {change:.2e})");
+    }
+
+    let u: Vec<f32> = GHIA_Y.iter().map(|&y| scene.velocity_at(0.5, y)[0]).collect();
+    let v: Vec<f32> = GHIA_X.iter().map(|&x| scene.velocity_at(x, 0.5)[1]).collect();
+    let u_error = ProfileError::new(&u, &GHIA_U[case]);
+    let v_error = ProfileError::new(&v, &GHIA_V[case]);
+
+    println!("  {:>7} {:>9} {:>9}   {:>7} {:>9} {:>9}", "y", "u", "Ghia", "x", "v", "Ghia");
+    for i in 0..GHIA_Y.len() {
+        println!(
+            "  {:>7.4} {:>9.5} {:>9.5}   {:>7.4} {:>9.5} {:>9.5}",
+            GHIA_Y[i], u[i], GHIA_U[case][i], GHIA_X[i], v[i], GHIA_V[case][i]
+        );
+    }
+    println!("  u: L2 error {:.4}, max error {:.4}", u_error.l2, u_error.max);
+    println!("  v: L2 error {:.4}, max error {:.4}", v_error.l2, v_error.max);
+    [u_error, v_error]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within_tolerance(case: usize, resolution: usize) {
+        let tolerance = tolerance(resolution).unwrap();
+        let [u_error, v_error] = run_case(case, resolution);
+        assert!(u_error.l2 <= tolerance, "u: L2 error {:.4}, tolerance {tolerance}", u_error.l2);
+        assert!(v_error.l2 <= tolerance, "v: L2 error {:.4}, tolerance {tolerance}", v_error.l2);
+    }
+
+    #[test]
+    fn reynolds_100_on_32_cells() {
+        assert_within_tolerance(0, 32);
+    }
+
+    #[test]
+    #[ignore = "runs to steady state; run with --release -- --ignored"]
+    fn reynolds_100_on_128_cells() {
+        assert_within_tolerance(0, 128);
+    }
+}
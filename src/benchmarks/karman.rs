@@ -0,0 +1,69 @@
+use crate::scenes::karman::{KarmanScene, PROBE_SAMPLES};
+use crate::scenes::Scene;
+
+/// Convective times (D / U) simulated before measuring, to let the wake develop.
+const WARMUP_CONVECTIVE_TIMES: usize = 100;
+/// Convective times used for the frequency measurement.
+const MEASURE_CONVECTIVE_TIMES: usize = 60;
+
+/// Runs the cylinder benchmark at `reynolds` and compares the measured Strouhal number with
+/// the reference. Returns whether the relative error is within `tolerance`.
+pub fn run(reynolds: f32, tolerance: f32) -> bool {
+    let mut scene = KarmanScene::new(reynolds);
+    println!("Karman vortex street benchmark at Re = {reynolds}");
+    let Some(strouhal) = measure(&mut scene) else {
+        return false;
+    };
+
+    let expected = scene.expected_strouhal();
+    let error = (strouhal - expected).abs() / expected;
+    let passed = error <= tolerance;
+    println!("Strouhal number: {strouhal:.4}");
+    println!("Reference:       {expected:.4} (Williamson 1988)");
+    println!("Relative error:  {:.1}% (tolerance {:.1}%)", error * 100.0, tolerance * 100.0);
+    println!("{}", if passed { "PASSED" } else { "FAILED" });
+    passed
+}
+
+/// Lets the wake of `scene` develop, then returns the Strouhal number measured over the
+/// following `MEASURE_CONVECTIVE_TIMES`, or `None` if no shedding was detected.
+fn measure(scene: &mut KarmanScene) -> Option<f32> {
+    let convective_time = scene.steps_per_convective_time();
+    let warmup = WARMUP_CONVECTIVE_TIMES * convective_time;
+    let window = MEASURE_CONVECTIVE_TIMES * convective_time;
+    assert!(window <= PROBE_SAMPLES, "the probe keeps too few samples for the measurement window");
+
+    for step in 0..warmup + window {
+        scene.update();
+        if (step + 1) % (10 * convective_time) == 0 {
+            println!("  t = {:>4} D/U", (step + 1) / convective_time);
+        }
+    }
+
+    let strouhal = scene.strouhal(window);
+    if strouhal.is_none() {
+        println!("No vortex shedding detected (probe amplitude {:.2e})", scene.probe.amplitude(window));
+    }
+    strouhal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// At full resolution the cylinder is twelve cells across and the shedding comes out about
+    /// 6% slow (St = 0.154 against 0.164), the rest of the way to Williamson's value being lost
+    /// to the numerical viscosity of the advection. Coarser grids do not resolve the wake well
+    /// enough to hold this tolerance.
+    const TOLERANCE: f32 = 0.1;
+
+    #[test]
+    #[cfg_attr(not(feature = "slow-tests"), ignore = "simulates 160 convective times; run with --release --features slow-tests")]
+    fn strouhal_number_within_ten_percent() {
+        let mut scene = KarmanScene::new(100.0);
+        let strouhal = measure(&mut scene).expect("the wake should shed vortices");
+        let expected = scene.expected_strouhal();
+        let error = (strouhal - expected).abs() / expected;
+        assert!(error <= TOLERANCE, "St = {strouhal:.4}, expected {expected:.4}");
+    }
+}
@@ -0,0 +1,50 @@
+use crate::scenes::mesh_tunnel::CAR_MESH;
+use crate::support::mesh::{Mesh, Plane};
+
+/// Loads an OBJ file, or the bundled car without one, and reports what the flow would see of
+/// it: its bounds, the loops of its cross-section through the middle along `z`, and how many
+/// voxels it fills at `resolution`. Returns whether the mesh loaded and produced a non-empty slice.
+pub fn run(path: Option<&str>, resolution: usize) -> bool {
+    let loaded = match path {
+        Some(path) => Mesh::load(path),
+        None => Mesh::parse(CAR_MESH),
+    };
+    let path = path.unwrap_or("car.obj");
+    let mesh = match loaded {
+        Ok(mesh) => mesh,
+        Err(error) => {
+            println!("Could not load {path}: {error}");
+            return false;
+        }
+    };
+
+    let (min, max) = mesh.bounds();
+    println!("{path}: {} triangles", mesh.triangles.len());
+    println!("  bounds {min:?} to {max:?}");
+
+    let depth = 0.5 * (min[2] + max[2]);
+    let loops = mesh.slice(&Plane::z(depth));
+    let area: f32 = loops.iter().map(|points| signed_area(points).abs()).sum();
+    println!("  slice at z = {depth}: {} loops, {} points, area {area:.4}", loops.len(), loops.iter().map(Vec::len).sum::<usize>());
+
+    let voxels = mesh.voxelize(resolution);
+    let volume = voxels.count() as f32 * voxels.voxel_size.powi(3);
+    println!(
+        "  voxels: {}x{}x{}, {} filled, volume {volume:.4}",
+        voxels.width,
+        voxels.height,
+        voxels.depth,
+        voxels.count()
+    );
+
+    !loops.is_empty()
+}
+
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    let mut area = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        area += a[0] * b[1] - b[0] * a[1];
+    }
+    0.5 * area
+}
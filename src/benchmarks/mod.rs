@@ -0,0 +1,6 @@
+//! Headless physics benchmarks and tools, run from the command line instead of opening a window.
+pub mod cavity;
+pub mod karman;
+pub mod mesh;
+pub mod sparse;
+pub mod taylor_green;
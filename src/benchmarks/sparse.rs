@@ -0,0 +1,75 @@
+use std::time::Instant;
+
+use crate::support::field::{ColorField2D, VectorField2D};
+use crate::support::sparse::{SparseColorField2D, SparseVectorField2D};
+
+const TIME_STEP: f32 = 1.0;
+const PLUME_SPEED: f32 = 1.5;
+/// Half-width of the rising column of air, in cells.
+const PLUME_RADIUS: f32 = 24.0;
+const SOURCE_RADIUS: f32 = 8.0;
+
+/// Carries smoke up a plume in the middle of a `size` x `size` room that is otherwise still,
+/// once with dense fields and once with sparse tiled ones, and compares their time, memory
+/// and results. Returns whether the sparse fields matched the dense ones and used less
+/// memory; the timings are only reported, as they depend on the machine and its load.
+pub fn run(size: usize, steps: usize) -> bool {
+    let velocity = plume(size);
+    let mut dense_velocity = VectorField2D::new(size, size, [0.0, 0.0]);
+    dense_velocity.field = velocity.to_grid();
+    let source = [size as f32 * 0.5, size as f32 - 2.0 * SOURCE_RADIUS];
+
+    println!("Smoke plume in a {size}x{size} room, {steps} steps");
+
+    let start = Instant::now();
+    let mut dense = ColorField2D::new(size, size, 0.0);
+    for _ in 0..steps {
+        dense.splat(source, SOURCE_RADIUS, 0.2);
+        dense = dense.update(&dense_velocity, TIME_STEP);
+    }
+    let dense_time = start.elapsed().as_secs_f32();
+
+    let start = Instant::now();
+    let mut sparse = SparseColorField2D::new(size, size, 0.0);
+    for _ in 0..steps {
+        sparse.splat(source, SOURCE_RADIUS, 0.2);
+        sparse = sparse.update(&velocity, TIME_STEP);
+    }
+    let sparse_time = start.elapsed().as_secs_f32();
+
+    let mut difference: f32 = 0.0;
+    for (row, dense_row) in sparse.to_grid().rows().zip(dense.field.rows()) {
+        for (value, dense_value) in row.iter().zip(dense_row) {
+            difference = difference.max((value - dense_value).abs());
+        }
+    }
+
+    let cell_bytes = std::mem::size_of::<f32>() + std::mem::size_of::<[f32; 2]>();
+    let dense_memory = size * size * cell_bytes;
+    let sparse_memory = sparse.memory_usage() + velocity.memory_usage();
+    println!("  {:>7} {:>10} {:>12}", "", "time (s)", "memory (KiB)");
+    println!("  {:>7} {dense_time:>10.3} {:>12}", "dense", dense_memory / 1024);
+    println!("  {:>7} {sparse_time:>10.3} {:>12}", "sparse", sparse_memory / 1024);
+    println!("  smoke tiles {} of {}, largest difference {difference:.2e}", sparse.active_tiles(), size.div_ceil(8).pow(2));
+
+    let passed = difference < 1e-4 && sparse_memory < dense_memory;
+    println!("{}", if passed { "PASSED" } else { "FAILED" });
+    passed
+}
+
+/// Column of rising air, fastest on its axis and still outside it, swaying from side to side.
+fn plume(size: usize) -> SparseVectorField2D {
+    let mut velocity = SparseVectorField2D::new(size, size, [0.0, 0.0]);
+    let axis = size as f32 * 0.5;
+    for y in 0..size {
+        for x in 0..size {
+            let offset = (x as f32 - axis) / PLUME_RADIUS;
+            if offset.abs() < 1.0 {
+                let profile = 1.0 - offset * offset;
+                let sway = 0.3 * (y as f32 / 40.0).sin();
+                velocity.set(x, y, [sway * profile, -PLUME_SPEED * profile]).expect("the loops stay inside the room");
+            }
+        }
+    }
+    velocity
+}
@@ -0,0 +1,85 @@
+use crate::support::real::Real;
+use crate::support::solver::FluidSolver;
+use crate::support::taylor_green::TaylorGreen;
+use crate::support::viscosity::ViscosityModel;
+
+pub const RESOLUTIONS: [usize; 4] = [16, 32, 64, 128];
+const AMPLITUDE: f64 = 1.0;
+const VISCOSITY: f64 = 0.1;
+const END_TIME: f64 = 1.0;
+/// Time step relative to the time the peak velocity takes to cross one cell.
+const COURANT: f64 = 0.5;
+/// Samples of the energy decay printed for each resolution.
+const REPORTS: usize = 4;
+
+/// Simulates the Taylor-Green vortex up to `END_TIME` at each resolution and prints the
+/// velocity error against the exact solution and the order of accuracy observed between
+/// successive resolutions, with fields of `precision` bits (32 or 64). Returns whether the
+/// order between the two finest grids is at least `min_order`.
+pub fn run(resolutions: &[usize], min_order: f64, precision: u32) -> bool {
+    println!("Taylor-Green vortex, nu = {VISCOSITY}, t = {END_TIME}, f{precision}");
+    let errors: Vec<f64> = resolutions
+        .iter()
+        .map(|&resolution| match precision {
+            64 => run_case::<f64>(resolution),
+            _ => run_case::<f32>(resolution),
+        })
+        .collect();
+
+    println!("  {:>10} {:>12} {:>8}", "resolution", "L2 error", "order");
+    let mut order = f64::NAN;
+    for (i, (resolution, error)) in resolutions.iter().zip(&errors).enumerate() {
+        if i == 0 {
+            println!("  {resolution:>10} {error:>12.3e} {:>8}", "-");
+        } else {
+            let ratio = *resolution as f64 / resolutions[i - 1] as f64;
+            order = (errors[i - 1] / error).ln() / ratio.ln();
+            println!("  {resolution:>10} {error:>12.3e} {order:>8.2}");
+        }
+    }
+
+    let passed = order >= min_order;
+    println!("Observed order {order:.2} (minimum {min_order:.2})");
+    println!("{}", if passed { "PASSED" } else { "FAILED" });
+    passed
+}
+
+/// Returns the L2 velocity error at `END_TIME`.
+fn run_case<T: Real>(resolution: usize) -> f64 {
+    let vortex = TaylorGreen::new(resolution, AMPLITUDE, VISCOSITY);
+    let mut solver = FluidSolver::<T>::new(vortex.size(), vortex.size());
+    solver.viscosity = ViscosityModel::Newtonian(T::from_f64(VISCOSITY));
+    solver.iterations = 2 * resolution;
+
+    let mut velocity_field = vortex.field(0.0);
+    let steps = (END_TIME * AMPLITUDE / (COURANT * vortex.cell_size())).ceil() as usize;
+    let delta_time = END_TIME / steps as f64;
+
+    println!("  {resolution}x{resolution} cells, {steps} steps");
+    for step in 1..=steps {
+        solver.step(&mut velocity_field, T::from_f64(delta_time));
+        if step % steps.div_ceil(REPORTS) == 0 || step == steps {
+            let time = step as f64 * delta_time;
+            println!(
+                "    t = {time:.3}  energy {:.5} (exact {:.5})  error {:.3e}",
+                vortex.measured_kinetic_energy(&velocity_field),
+                vortex.kinetic_energy(time),
+                vortex.error(&velocity_field, time)
+            );
+        }
+    }
+    vortex.error(&velocity_field, steps as f64 * delta_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Semi-Lagrangian advection is first order, which already shows between the two coarsest grids.
+    #[test]
+    fn first_order_between_coarse_grids() {
+        let (coarse, fine) = (run_case::<f64>(16), run_case::<f64>(32));
+        let order = (coarse / fine).log2();
+        assert!((0.8..1.2).contains(&order), "observed order {order:.2}");
+    }
+}
@@ -1,12 +1,17 @@
 #[macro_use]
 extern crate glium;
 mod support;
+mod scenes;
+mod benchmarks;
 
 use glium::index::PrimitiveType;
 use glium::{Display, Surface};
 use glutin::surface::WindowSurface;
+use scenes::{ColorMode, Marker, Scene, SCENES};
 use support::{ApplicationContext, State};
-use support::field::{ColorField2D, VectorField2D};
+use support::diagnostics::{Diagnostics, DiagnosticsLog};
+use support::field::VectorField2D;
+use support::particles::TracerSystem;
 
 #[derive(Copy, Clone)]
 struct Vertex {
@@ -20,57 +25,50 @@ struct Application {
     pub index_buffer: glium::IndexBuffer<u32>,
     pub program: glium::Program,
     pub color_matrix: Vec<Vec<[f32; 3]>>,
-    pub time: f32,
-    pub velocity_field: VectorField2D
+    pub scene: Box<dyn Scene>,
+    pub scene_index: usize,
+    pub show_arrows: bool,
+    /// Tracer particles following the scene's velocity field, when enabled.
+    pub tracers: Option<TracerSystem>,
+    /// Steps taken by the current scene.
+    pub steps: usize,
+    pub print_diagnostics: bool,
+    pub diagnostics_log: Option<DiagnosticsLog>,
+    /// Grid size relative to each scene's built-in resolution, changed with - and = and
+    /// kept when switching or restarting scenes.
+    pub resolution_scale: f32,
 }
 
-fn generate_color_matrix(grid_size: usize, time: f32) -> Vec<Vec<[f32; 3]>> {
-    let mut color_matrix = vec![vec![[0.0, 0.0, 0.0]; grid_size]; grid_size];
+/// Steps between printed diagnostics; the log gets every step.
+const DIAGNOSTICS_INTERVAL: usize = 30;
+const DIAGNOSTICS_LOG: &str = "diagnostics.csv";
+/// Range of grid sizes, along the longer side, reachable with the resolution keys.
+const MIN_RESOLUTION: usize = 16;
+const MAX_RESOLUTION: usize = 512;
 
-    for row in 0..grid_size {
-        for col in 0..grid_size {
-            color_matrix[row][col] = [
-                (row as f32 / grid_size as f32) + time.sin() * 0.1,  
-                (col as f32 / grid_size as f32) + time.cos() * 0.1,  
-                ((row + col) as f32 / (2 * grid_size) as f32) + time.sin() * 0.1 
-            ];
-        }
-    }
-
-    color_matrix
-}
-
-fn generate_grid_data(grid_size: usize, cell_size: f32, color_matrix: &Vec<Vec<[f32; 3]>>) -> (Vec<Vertex>, Vec<u32>) {
+/// Stretches a `[row][col]` color matrix over the whole viewport, row 0 at the top.
+fn generate_grid_data(color_matrix: &[Vec<[f32; 3]>]) -> (Vec<Vertex>, Vec<u32>) {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
 
-    for row in 0..grid_size {
-        for col in 0..grid_size {
-            let x = 1.0 - col as f32 * cell_size;
-            let y = 1.0 - row as f32 * cell_size;
+    let rows = color_matrix.len();
+    let cols = color_matrix.first().map_or(0, |row| row.len());
+    let cell_width = 2.0 / cols as f32;
+    let cell_height = 2.0 / rows as f32;
 
-            let cell_color = color_matrix[row][col];
+    for (row, colors) in color_matrix.iter().enumerate() {
+        for (col, &color) in colors.iter().enumerate() {
+            let x = -1.0 + col as f32 * cell_width;
+            let y = 1.0 - row as f32 * cell_height;
 
             let v0 = vertices.len() as u32;
-            vertices.push(Vertex {
-                position: [x, y],
-                color: cell_color,
-            });
-            vertices.push(Vertex {
-                position: [x + cell_size, y],
-                color: cell_color,
-            });
-            vertices.push(Vertex {
-                position: [x + cell_size, y - cell_size],
-                color: cell_color,
-            });
-            vertices.push(Vertex {
-                position: [x, y - cell_size],
-                color: cell_color,
-            });
+            vertices.push(Vertex { position: [x, y], color });
+            vertices.push(Vertex { position: [x + cell_width, y], color });
+            vertices.push(Vertex { position: [x + cell_width, y - cell_height], color });
+            vertices.push(Vertex { position: [x, y - cell_height], color });
 
             indices.extend_from_slice(&[
-                v0, v0 + 1, v0 + 2, 
+                v0, v0 + 1, v0 + 2,
                 v0, v0 + 2, v0 + 3,
             ]);
         }
@@ -79,18 +77,23 @@ fn generate_grid_data(grid_size: usize, cell_size: f32, color_matrix: &Vec<Vec<[
     (vertices, indices)
 }
 
-fn generate_arrows(grid_size: usize, cell_size: f32, velocity_field: &VectorField2D) -> (Vec<Vertex>, Vec<u32>) {
+/// Draws one arrow every `stride` cells, scaled so that a speed of `max_speed` spans `stride` cells.
+fn generate_arrows(velocity_field: &VectorField2D, stride: usize, max_speed: f32) -> (Vec<Vertex>, Vec<u32>) {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
 
-    for row in 0..velocity_field.height {
-        for col in 0..velocity_field.width {
-            let x = 1.0 - col as f32 * cell_size;
-            let y = 1.0 - row as f32 * cell_size;
+    let cell_width = 2.0 / velocity_field.width() as f32;
+    let cell_height = 2.0 / velocity_field.height() as f32;
+    let scale = stride as f32 / max_speed;
+
+    for row in (stride / 2..velocity_field.height()).step_by(stride) {
+        for col in (stride / 2..velocity_field.width()).step_by(stride) {
+            let x = -1.0 + (col as f32 + 0.5) * cell_width;
+            let y = 1.0 - (row as f32 + 0.5) * cell_height;
 
             let direction = velocity_field.field[row][col];
-            let dx = direction[0] * 0.05;
-            let dy = direction[1] * 0.05;
+            let dx = direction[0] * scale * cell_width;
+            let dy = -direction[1] * scale * cell_height;
 
             let start = vertices.len() as u32;
             vertices.push(Vertex {
@@ -137,29 +140,76 @@ fn generate_arrows(grid_size: usize, cell_size: f32, velocity_field: &VectorFiel
     (vertices, indices)
 }
 
+/// Draws each tracer's trail as line segments fading out with age, and its head as a small cross.
+fn generate_tracers(tracers: &TracerSystem) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let cell_width = 2.0 / tracers.width as f32;
+    let cell_height = 2.0 / tracers.height as f32;
+    let to_screen = |p: [f32; 2]| {
+        let [x, y] = tracers.geometry.index(p);
+        [-1.0 + (x + 0.5) * cell_width, 1.0 - (y + 0.5) * cell_height]
+    };
+
+    for tracer in &tracers.tracers {
+        let mut previous = to_screen(tracer.position);
+        for (i, &point) in tracer.trail.iter().enumerate() {
+            let fade = 1.0 - i as f32 / tracers.trail_length as f32;
+            let point = to_screen(point);
+
+            let start = vertices.len() as u32;
+            vertices.push(Vertex { position: previous, color: [0.8 * fade, 0.9 * fade, fade] });
+            vertices.push(Vertex { position: point, color: [0.7 * fade, 0.8 * fade, fade] });
+            indices.extend_from_slice(&[start, start + 1]);
+            previous = point;
+        }
+
+        let [x, y] = to_screen(tracer.position);
+        let (dx, dy) = (cell_width * 0.4, cell_height * 0.4);
+        let start = vertices.len() as u32;
+        for position in [[x - dx, y], [x + dx, y], [x, y - dy], [x, y + dy]] {
+            vertices.push(Vertex { position, color: [1.0, 1.0, 1.0] });
+        }
+        indices.extend_from_slice(&[start, start + 1, start + 2, start + 3]);
+    }
+
+    (vertices, indices)
+}
+
+/// Draws each marker as a cross `size` cells wide on a grid of `width` by `height` cells.
+fn generate_markers(markers: &[Marker], width: usize, height: usize) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let cell_width = 2.0 / width as f32;
+    let cell_height = 2.0 / height as f32;
+
+    for marker in markers {
+        let x = -1.0 + (marker.position[0] + 0.5) * cell_width;
+        let y = 1.0 - (marker.position[1] + 0.5) * cell_height;
+        let (dx, dy) = (cell_width * marker.size * 0.5, cell_height * marker.size * 0.5);
+
+        let start = vertices.len() as u32;
+        for position in [[x - dx, y], [x + dx, y], [x, y - dy], [x, y + dy]] {
+            vertices.push(Vertex { position, color: marker.color });
+        }
+        indices.extend_from_slice(&[start, start + 1, start + 2, start + 3]);
+    }
+
+    (vertices, indices)
+}
+
 impl ApplicationContext for Application {
     const WINDOW_TITLE: &'static str = "Glium grid example";
 
 
     fn new(display: &Display<WindowSurface>) -> Self {
-        //-- Field
-        let fieldWidth = 40;
-        let fieldHeight = 40;
-
-        let initial_color = 1.0;
-        let mut velocity_field = VectorField2D {
-            width: fieldWidth,
-            height: fieldHeight,
-            field: vec![vec![[0.5, 0.5]; fieldWidth]; fieldHeight],
-        };
-
-        // -- Grid
-            
-        let grid_size = 201; 
-        let cell_size = 0.01;
+        let scene = SCENES[0]();
+        println!("Scene: {}", scene.name());
 
-        let color_matrix = generate_color_matrix(grid_size, 0.0);
-        let (vertices, indices) = generate_grid_data(grid_size, cell_size, &color_matrix);
+        let color_matrix = scene.color_matrix();
+        let (vertices, indices) = generate_grid_data(&color_matrix);
 
         let vertex_buffer = glium::VertexBuffer::new(display, &vertices).unwrap();
         let index_buffer = glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &indices).unwrap();
@@ -182,10 +232,29 @@ impl ApplicationContext for Application {
 
                 fragment: "
                     #version 100
+                    precision mediump float;
+
+                    uniform int color_mode;
                     varying lowp vec3 vColor;
 
+                    // Approximate color of a black body heated from 1000K (t = 0) to 6500K (t = 1).
+                    vec3 blackbody(float t) {
+                        float kelvin = mix(1000.0, 6500.0, t) / 100.0;
+                        float green = clamp(0.39008157 * log(kelvin) - 0.63184144, 0.0, 1.0);
+                        float blue = kelvin <= 19.0 ? 0.0 : clamp(0.54320678 * log(kelvin - 10.0) - 1.19625408, 0.0, 1.0);
+                        float glow = clamp(t * 2.5, 0.0, 1.0);
+                        return vec3(1.0, green, blue) * glow * glow;
+                    }
+
                     void main() {
-                        gl_FragColor = vec4(vColor, 1.0);
+                        if (color_mode == 1) {
+                            float t = clamp(vColor.r, 0.0, 1.0);
+                            vec3 fire = blackbody(t);
+                            float smoke = clamp(vColor.g, 0.0, 1.0) * (1.0 - clamp(t * 2.5, 0.0, 1.0));
+                            gl_FragColor = vec4(mix(fire, vec3(0.3), smoke), 1.0);
+                        } else {
+                            gl_FragColor = vec4(vColor, 1.0);
+                        }
                     }
                 ",
             },
@@ -197,54 +266,300 @@ impl ApplicationContext for Application {
             index_buffer,
             program,
             color_matrix,
-            time: 0.0,
-            velocity_field
+            scene,
+            scene_index: 0,
+            show_arrows: false,
+            tracers: None,
+            steps: 0,
+            print_diagnostics: false,
+            diagnostics_log: None,
+            resolution_scale: 1.0,
+        }
+    }
+
+    fn update(&mut self) {
+        self.scene.update();
+
+        if let (Some(tracers), Some(velocity_field)) = (&mut self.tracers, self.scene.velocity_field()) {
+            tracers.update(velocity_field, self.scene.time_step());
+        }
+
+        self.steps += 1;
+        self.record_diagnostics();
+    }
+
+    fn handle_window_event(&mut self, event: &glium::winit::event::WindowEvent, _window: &glium::winit::window::Window) {
+        use glium::winit::keyboard::{KeyCode, PhysicalKey};
+        let glium::winit::event::WindowEvent::KeyboardInput { event, .. } = event else {
+            return
+        };
+        if event.state != glium::winit::event::ElementState::Pressed || event.repeat {
+            return;
+        }
+        match event.physical_key {
+            // Tab cycles through the scenes, R restarts the current one, V toggles velocity
+            // arrows, T toggles tracer particles, D toggles printed diagnostics, L toggles
+            // the diagnostics log, and - and = halve and double the grid resolution.
+            PhysicalKey::Code(KeyCode::Tab) => {
+                self.scene_index = (self.scene_index + 1) % SCENES.len();
+            }
+            PhysicalKey::Code(KeyCode::KeyR) => (),
+            PhysicalKey::Code(KeyCode::KeyV) => {
+                self.show_arrows = !self.show_arrows;
+                return;
+            }
+            PhysicalKey::Code(KeyCode::KeyT) => {
+                self.tracers = match self.tracers {
+                    Some(_) => None,
+                    None => self.scene.velocity_field().map(new_tracers),
+                };
+                return;
+            }
+            PhysicalKey::Code(KeyCode::KeyD) => {
+                self.print_diagnostics = !self.print_diagnostics;
+                return;
+            }
+            PhysicalKey::Code(KeyCode::KeyL) => {
+                self.diagnostics_log = match self.diagnostics_log.take() {
+                    Some(mut log) => {
+                        if let Err(error) = log.flush() {
+                            eprintln!("Could not write {DIAGNOSTICS_LOG}: {error}");
+                        }
+                        println!("Stopped logging diagnostics");
+                        None
+                    }
+                    None => match DiagnosticsLog::create(DIAGNOSTICS_LOG) {
+                        Ok(log) => {
+                            println!("Logging diagnostics to {DIAGNOSTICS_LOG}");
+                            Some(log)
+                        }
+                        Err(error) => {
+                            eprintln!("Could not create {DIAGNOSTICS_LOG}: {error}");
+                            None
+                        }
+                    },
+                };
+                return;
+            }
+            PhysicalKey::Code(KeyCode::Minus) => {
+                self.change_resolution(0.5);
+                return;
+            }
+            PhysicalKey::Code(KeyCode::Equal) => {
+                self.change_resolution(2.0);
+                return;
+            }
+            PhysicalKey::Code(code) => {
+                self.scene.handle_key(code);
+                return;
+            }
+            _ => return,
+        }
+        self.scene = SCENES[self.scene_index]();
+        self.steps = 0;
+        println!("Scene: {}", self.scene.name());
+        self.color_matrix = self.scene.color_matrix();
+        if self.resolution_scale != 1.0 {
+            self.resize_scene(self.resolution_scale);
+        }
+        if self.tracers.is_some() {
+            self.tracers = self.scene.velocity_field().map(new_tracers);
         }
     }
 
     fn draw_frame(&mut self, display: &Display<WindowSurface>) {
         let mut frame = display.draw();
         frame.clear_color(0.0, 0.0, 0.0, 1.0);
-        self.time += 0.01;
-        let grid_size = 201; 
-        let cell_size = 0.01;
-        let color_matrix = generate_color_matrix(grid_size, self.time);
-        let (vertices, indices) = generate_grid_data(grid_size, cell_size, &color_matrix);
-        let vertex_buffer = glium::VertexBuffer::new(display, &vertices).unwrap();
-        let index_buffer = glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &indices).unwrap();
+
+        self.color_matrix = self.scene.color_matrix();
+        let (vertices, indices) = generate_grid_data(&self.color_matrix);
+        self.vertex_buffer = glium::VertexBuffer::new(display, &vertices).unwrap();
+        self.index_buffer = glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &indices).unwrap();
 
         frame
             .draw(
-                &vertex_buffer,
-                &index_buffer,
+                &self.vertex_buffer,
+                &self.index_buffer,
                 &self.program,
-                &uniform! {},
+                &uniform! { color_mode: self.scene.color_mode().uniform() },
                 &Default::default(),
             )
             .unwrap();
 
-        let cell_size_field = 0.05;
+        if let (true, Some(velocity_field)) = (self.show_arrows, self.scene.velocity_field()) {
+            let (arrow_vertices, arrow_indices) = generate_arrows(velocity_field, 4, 10.0);
+            let arrow_vertex_buffer = glium::VertexBuffer::new(display, &arrow_vertices).unwrap();
+            let arrow_index_buffer = glium::IndexBuffer::new(display, PrimitiveType::LinesList, &arrow_indices).unwrap();
+
+            frame
+                .draw(
+                    &arrow_vertex_buffer,
+                    &arrow_index_buffer,
+                    &self.program,
+                    &uniform! { color_mode: ColorMode::Rgb.uniform() },
+                    &Default::default(),
+                )
+                .unwrap();
+        }
 
-        let (arrow_vertices, arrow_indices) = generate_arrows(self.velocity_field.width, cell_size_field, &self.velocity_field);
-        let arrow_vertex_buffer = glium::VertexBuffer::new(display, &arrow_vertices).unwrap();
-        let arrow_index_buffer = glium::IndexBuffer::new(display, PrimitiveType::LinesList, &arrow_indices).unwrap();
+        let markers = self.scene.markers();
+        if !markers.is_empty() {
+            let rows = self.color_matrix.len();
+            let cols = self.color_matrix.first().map_or(0, |row| row.len());
+            let (marker_vertices, marker_indices) = generate_markers(&markers, cols, rows);
+            let marker_vertex_buffer = glium::VertexBuffer::new(display, &marker_vertices).unwrap();
+            let marker_index_buffer = glium::IndexBuffer::new(display, PrimitiveType::LinesList, &marker_indices).unwrap();
+
+            frame
+                .draw(
+                    &marker_vertex_buffer,
+                    &marker_index_buffer,
+                    &self.program,
+                    &uniform! { color_mode: ColorMode::Rgb.uniform() },
+                    &Default::default(),
+                )
+                .unwrap();
+        }
 
-        frame
-            .draw(
-                &arrow_vertex_buffer,
-                &arrow_index_buffer,
-                &self.program,
-                &uniform! {},
-                &Default::default(),
-            )
-            .unwrap();
+        if let Some(tracers) = &self.tracers {
+            let (tracer_vertices, tracer_indices) = generate_tracers(tracers);
+            let tracer_vertex_buffer = glium::VertexBuffer::new(display, &tracer_vertices).unwrap();
+            let tracer_index_buffer = glium::IndexBuffer::new(display, PrimitiveType::LinesList, &tracer_indices).unwrap();
+
+            frame
+                .draw(
+                    &tracer_vertex_buffer,
+                    &tracer_index_buffer,
+                    &self.program,
+                    &uniform! { color_mode: ColorMode::Rgb.uniform() },
+                    &Default::default(),
+                )
+                .unwrap();
+        }
 
         frame.finish().unwrap();
         
     }
 }
 
+impl Application {
+    /// Resamples the current scene to `scale` times its grid size, and keeps that size for
+    /// the scenes started afterwards.
+    fn change_resolution(&mut self, scale: f32) {
+        if let Some(applied) = self.resize_scene(scale) {
+            self.resolution_scale *= applied;
+        }
+    }
+
+    /// Resamples the current scene to `scale` times its grid size, within the allowed range.
+    /// Returns the scale actually applied, if the scene could be resampled.
+    fn resize_scene(&mut self, scale: f32) -> Option<f32> {
+        let height = self.color_matrix.len();
+        let width = self.color_matrix.first().map_or(0, |row| row.len());
+        let longest = width.max(height) as f32;
+        let scale = (scale * longest).clamp(MIN_RESOLUTION as f32, MAX_RESOLUTION as f32) / longest;
+        let new_width = ((width as f32 * scale).round() as usize).max(3);
+        let new_height = ((height as f32 * scale).round() as usize).max(3);
+        if (new_width, new_height) == (width, height) {
+            return None;
+        }
+
+        if !self.scene.resample(new_width, new_height) {
+            println!("{} only runs at {width}x{height}", self.scene.name());
+            return None;
+        }
+        self.color_matrix = self.scene.color_matrix();
+        let resampled_width = self.color_matrix.first().map_or(0, |row| row.len());
+        println!("Resolution: {resampled_width}x{}", self.color_matrix.len());
+        if self.tracers.is_some() {
+            self.tracers = self.scene.velocity_field().map(new_tracers);
+        }
+        Some(resampled_width as f32 / width as f32)
+    }
+
+    fn record_diagnostics(&mut self) {
+        let print = self.print_diagnostics && self.steps.is_multiple_of(DIAGNOSTICS_INTERVAL);
+        if !print && self.diagnostics_log.is_none() {
+            return;
+        }
+        let Some(velocity_field) = self.scene.velocity_field() else {
+            return;
+        };
+
+        let time_step = self.scene.time_step();
+        let diagnostics = Diagnostics::measure(velocity_field, self.scene.dye().as_ref(), time_step);
+        if print {
+            println!("{:>6}  {diagnostics}", self.steps);
+            if let Some(report) = self.scene.report() {
+                println!("{:>6}  {report}", "");
+            }
+        }
+        if let Some(log) = &mut self.diagnostics_log {
+            if let Err(error) = log.write(self.steps, self.steps as f32 * time_step, &diagnostics) {
+                eprintln!("Could not write {DIAGNOSTICS_LOG}: {error}");
+                self.diagnostics_log = None;
+            }
+        }
+    }
+}
+
+fn new_tracers(velocity_field: &VectorField2D) -> TracerSystem {
+    TracerSystem::new(velocity_field, 800)
+}
+
 fn main() {
-    
-    State::<Application>::run_loop();
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let option = |name: &str, default: f32| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .map_or(default, |value| value.parse().expect("expected a number"))
+    };
+
+    match args.first().map(String::as_str) {
+        // cargo run --release -- karman [--re 100] [--tolerance 0.1]
+        Some("karman") => {
+            let passed = benchmarks::karman::run(option("--re", 100.0), option("--tolerance", 0.1));
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        // cargo run --release -- cavity [--re 100|400|1000] [--resolution 64] [--tolerance <0.04 at 32, 0.02 at 64, 0.01 from 128>]
+        Some("cavity") => {
+            let given = |name: &str| args.iter().any(|arg| arg == name);
+            let reynolds_numbers = match given("--re") {
+                true => vec![option("--re", 100.0)],
+                false => benchmarks::cavity::REYNOLDS_NUMBERS.to_vec(),
+            };
+            let tolerance = given("--tolerance").then(|| option("--tolerance", 0.0));
+            let passed = benchmarks::cavity::run(&reynolds_numbers, option("--resolution", 64.0) as usize, tolerance);
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        // cargo run --release -- taylor-green [--min-order 0.8] [--precision 32|64]
+        Some("taylor-green") => {
+            let passed = benchmarks::taylor_green::run(
+                &benchmarks::taylor_green::RESOLUTIONS,
+                option("--min-order", 0.8).into(),
+                option("--precision", 32.0) as u32,
+            );
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        // cargo run --release -- mesh [path/to/model.obj] [--resolution 64]
+        Some("mesh") => {
+            // The path is the first argument that is neither an option nor an option's value.
+            let path = (1..args.len())
+                .find(|&i| !args[i].starts_with("--") && !args[i - 1].starts_with("--"))
+                .map(|i| args[i].as_str());
+            let passed = benchmarks::mesh::run(path, option("--resolution", 64.0) as usize);
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        // cargo run --release -- sparse [--size 1024] [--steps 300]
+        Some("sparse") => {
+            let passed = benchmarks::sparse::run(option("--size", 1024.0) as usize, option("--steps", 300.0) as usize);
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        Some(command) => {
+            eprintln!("Unknown command: {command}");
+            std::process::exit(2);
+        }
+        None => State::<Application>::run_loop(),
+    }
+}
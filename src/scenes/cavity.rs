@@ -0,0 +1,137 @@
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::solver::{EdgeCondition, FluidSolver};
+use crate::support::turbulence::Smagorinsky;
+use crate::support::viscosity::ViscosityModel;
+
+pub const TIME_STEP: f32 = 0.5;
+const LID_SPEED: f32 = 1.0;
+
+/// Square cavity whose top wall slides to the right, driving a large recirculating
+/// vortex with smaller corner eddies. The cavity has `resolution` cells per side inside a
+/// ring of edge cells, and the Reynolds number is based on the lid speed and cavity size.
+pub struct LidDrivenCavityScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub reynolds: f32,
+    pub resolution: usize,
+    pub steps: usize,
+}
+
+impl LidDrivenCavityScene {
+    pub fn new(reynolds: f32, resolution: usize) -> Self {
+        let size = resolution + 2;
+        let mut solver = FluidSolver::new(size, size);
+        solver.viscosity = ViscosityModel::Newtonian(LID_SPEED * resolution as f32 / reynolds);
+        solver.iterations = 80;
+        let wall = EdgeCondition::MovingWall([0.0, 0.0]);
+        solver.edges = [wall, wall, EdgeCondition::MovingWall([LID_SPEED, 0.0]), wall];
+
+        Self {
+            velocity_field: VectorField2D::new(size, size, [0.0, 0.0]),
+            solver,
+            reynolds,
+            resolution,
+            steps: 0,
+        }
+    }
+
+    /// Cavity at a Reynolds number too high for the grid to resolve, with a dynamic
+    /// Smagorinsky model standing in for the eddies smaller than a cell.
+    pub fn turbulent(reynolds: f32, resolution: usize) -> Self {
+        let mut scene = Self::new(reynolds, resolution);
+        scene.solver.turbulence = Some(Smagorinsky::dynamic());
+        scene
+    }
+
+    /// Advances one step and returns the largest velocity change per unit time, relative
+    /// to the lid speed, as a measure of how far the flow is from steady state.
+    pub fn advance(&mut self) -> f32 {
+        let previous = self.velocity_field.field.clone();
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.steps += 1;
+
+        let mut change: f32 = 0.0;
+        for (row, previous_row) in self.velocity_field.field.rows().zip(previous.rows()) {
+            for (velocity, previous) in row.iter().zip(previous_row) {
+                change = change.max((velocity[0] - previous[0]).abs()).max((velocity[1] - previous[1]).abs());
+            }
+        }
+        change / (TIME_STEP * LID_SPEED)
+    }
+
+    /// Velocity normalised by the lid speed at a point of the unit cavity, with the origin
+    /// at the bottom-left corner and `y` pointing up, as in the usual reference data.
+    pub fn velocity_at(&self, x: f32, y: f32) -> [f32; 2] {
+        let n = self.resolution as f32;
+        // Cell `i` is centred at `(i - 0.5) / n`; the walls sit half a cell outside the first and last cells.
+        let [u, v] = self.velocity_field.bilinear_interpolation(0.5 + x * n, 0.5 + (1.0 - y) * n);
+        [u / LID_SPEED, -v / LID_SPEED]
+    }
+
+    /// Time for the lid to travel across the cavity, in steps.
+    pub fn steps_per_convective_time(&self) -> usize {
+        (self.resolution as f32 / LID_SPEED / TIME_STEP).round() as usize
+    }
+}
+
+impl Scene for LidDrivenCavityScene {
+    fn name(&self) -> &'static str {
+        match self.solver.turbulence {
+            Some(_) => "Lid-driven cavity (LES)",
+            None => "Lid-driven cavity",
+        }
+    }
+
+    fn update(&mut self) {
+        self.advance();
+    }
+
+    fn time_step(&self) -> f32 {
+        TIME_STEP
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        (0..self.velocity_field.height())
+            .map(|y| {
+                (0..self.velocity_field.width())
+                    .map(|x| {
+                        let [u, v] = self.velocity_field.field[y][x];
+                        let speed = ((u * u + v * v).sqrt() / LID_SPEED).min(1.0);
+                        let vorticity = (self.velocity_field.curl(x, y) * self.resolution as f32 * 0.05).clamp(-1.0, 1.0);
+                        [
+                            0.1 + 0.6 * speed + 0.3 * vorticity.max(0.0),
+                            0.1 + 0.5 * speed,
+                            0.2 + 0.3 * speed + 0.5 * (-vorticity).max(0.0),
+                        ]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    /// The cavity stays square inside its ring of edge cells, so it is rebuilt with
+    /// `min(width, height) - 2` cells per side at the same Reynolds number, and the flow is
+    /// carried over point by point in cavity coordinates.
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        let resolution = width.min(height).saturating_sub(2).max(1);
+        let mut resampled = Self::new(self.reynolds, resolution);
+        resampled.solver.turbulence = self.solver.turbulence;
+        resampled.steps = self.steps;
+
+        let n = resolution as f32;
+        for (y, row) in resampled.velocity_field.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let [u, v] = self.velocity_at((x as f32 - 0.5) / n, 1.0 - (y as f32 - 0.5) / n);
+                *value = [u * LID_SPEED, -v * LID_SPEED];
+            }
+        }
+        resampled.solver.project(&mut resampled.velocity_field);
+        *self = resampled;
+        true
+    }
+}
@@ -0,0 +1,128 @@
+use glium::winit::keyboard::KeyCode;
+
+use super::{ColorMode, Scene};
+use crate::support::combustion::Combustion;
+use crate::support::curl_noise::CurlNoise;
+use crate::support::field::{ColorField2D, VectorField2D};
+use crate::support::solver::FluidSolver;
+
+const SIZE: usize = 96;
+const TIME_STEP: f32 = 0.1;
+/// Temperature drawn as white-hot by the blackbody ramp.
+const MAX_TEMPERATURE: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirePreset {
+    /// A small wick that keeps feeding and heating fuel at the bottom of the domain.
+    Candle,
+    /// A single ball of fuel ignited at once and left to burn out.
+    Fireball,
+}
+
+pub struct FireScene {
+    pub preset: FirePreset,
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub combustion: Combustion,
+    /// Small eddies stirred into the hot gas, which the grid is too coarse to produce by
+    /// itself. Toggled with N.
+    pub turbulence: CurlNoise,
+    pub turbulence_enabled: bool,
+}
+
+impl FireScene {
+    pub fn new(preset: FirePreset) -> Self {
+        let mut combustion = Combustion::new(SIZE, SIZE);
+        if preset == FirePreset::Fireball {
+            let center = SIZE as f32 * 0.5;
+            combustion.add_fuel([center, SIZE as f32 * 0.7], 14.0, 3.0);
+            combustion.add_heat([center, SIZE as f32 * 0.7], 6.0, 1.0);
+            combustion.smoke_yield = 0.6;
+        }
+
+        let mut turbulence = CurlNoise::new(7);
+        turbulence.scale = 16.0;
+        turbulence.strength = 2.0;
+
+        Self {
+            preset,
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver: FluidSolver::new(SIZE, SIZE),
+            combustion,
+            turbulence,
+            turbulence_enabled: true,
+        }
+    }
+
+    pub fn candle() -> Self {
+        Self::new(FirePreset::Candle)
+    }
+
+    pub fn fireball() -> Self {
+        Self::new(FirePreset::Fireball)
+    }
+}
+
+impl Scene for FireScene {
+    fn name(&self) -> &'static str {
+        match self.preset {
+            FirePreset::Candle => "Candle flame",
+            FirePreset::Fireball => "Fireball",
+        }
+    }
+
+    fn update(&mut self) {
+        if self.preset == FirePreset::Candle {
+            let wick = (SIZE as f32 * 0.5, SIZE as f32 * 0.85);
+            self.combustion.add_fuel([wick.0, wick.1], 3.0, 0.6 * TIME_STEP);
+            self.combustion.add_heat([wick.0, wick.1 + 1.0], 2.0, 0.4 * TIME_STEP);
+        }
+
+        self.combustion.update(&mut self.velocity_field, TIME_STEP);
+        if self.turbulence_enabled {
+            self.turbulence.apply(&mut self.velocity_field, Some(&self.combustion.temperature), TIME_STEP);
+        }
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        let temperature = &self.combustion.temperature.field;
+        let smoke = &self.combustion.smoke.field;
+
+        temperature
+            .rows()
+            .zip(smoke.rows())
+            .map(|(temperature_row, smoke_row)| {
+                temperature_row
+                    .iter()
+                    .zip(smoke_row)
+                    .map(|(t, s)| [t / MAX_TEMPERATURE, *s, 0.0])
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn color_mode(&self) -> ColorMode {
+        ColorMode::Blackbody
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn dye(&self) -> Option<ColorField2D> {
+        Some(self.combustion.smoke.clone())
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.combustion.resample(width, height);
+        true
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        if key == KeyCode::KeyN {
+            self.turbulence_enabled = !self.turbulence_enabled;
+        }
+    }
+}
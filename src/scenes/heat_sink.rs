@@ -0,0 +1,97 @@
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::heat::{HeatTransfer, ThermalWall};
+use crate::support::solver::FluidSolver;
+use crate::support::viscosity::ViscosityModel;
+
+const SIZE: usize = 96;
+const TIME_STEP: f32 = 0.1;
+const AIR_CONDUCTIVITY: f32 = 0.02;
+const ALUMINIUM_CONDUCTIVITY: f32 = 20.0;
+const ALUMINIUM_HEAT_CAPACITY: f32 = 2.5;
+/// Temperature drawn at the hot end of the color map.
+const MAX_TEMPERATURE: f32 = 0.5;
+
+/// A chip under a finned aluminium heat sink in a closed box of air with a cold ceiling.
+/// Heat conducts from the chip through the fins and is carried away by natural convection.
+pub struct HeatSinkScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub heat: HeatTransfer,
+}
+
+impl HeatSinkScene {
+    pub fn new() -> Self {
+        let mut solver = FluidSolver::new(SIZE, SIZE);
+        solver.viscosity = ViscosityModel::Newtonian(0.02);
+
+        // Chip and base plate, then six fins rising from the plate.
+        solver.add_solid_rectangle(40, 84, 56, 88);
+        solver.add_solid_rectangle(28, 80, 68, 84);
+        for fin in 0..6 {
+            let x = 29 + fin * 7;
+            solver.add_solid_rectangle(x, 56, x + 2, 80);
+        }
+
+        let mut heat = HeatTransfer::new(SIZE, SIZE, 0.0, AIR_CONDUCTIVITY);
+        heat.set_material(&solver.solid, ALUMINIUM_CONDUCTIVITY, ALUMINIUM_HEAT_CAPACITY);
+        for y in 84..88 {
+            for source in &mut heat.heat_source.field[y][40..56] {
+                *source = 0.05;
+            }
+        }
+        heat.walls = [ThermalWall::Insulated, ThermalWall::Insulated, ThermalWall::Fixed(0.0), ThermalWall::Insulated];
+        heat.expansion = 2.0;
+
+        Self {
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver,
+            heat,
+        }
+    }
+}
+
+impl Scene for HeatSinkScene {
+    fn name(&self) -> &'static str {
+        "Heat sink in natural convection"
+    }
+
+    fn update(&mut self) {
+        self.heat.update(&mut self.velocity_field, &self.solver.solid, TIME_STEP);
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        self.heat
+            .temperature
+            .field
+            .rows()
+            .zip(self.solver.solid.rows())
+            .map(|(temperatures, solids)| {
+                temperatures
+                    .iter()
+                    .zip(solids)
+                    .map(|(&temperature, &solid)| {
+                        let t = (temperature / MAX_TEMPERATURE).clamp(0.0, 1.0);
+                        let color = [t.sqrt(), t * t, 0.4 * (1.0 - t) + 0.1];
+                        if solid {
+                            color.map(|c| 0.6 * c + 0.3)
+                        } else {
+                            color
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.heat.resample(width, height);
+        true
+    }
+}
@@ -0,0 +1,102 @@
+use super::Scene;
+use crate::support::field::{ColorField2D, RgbField2D, VectorField2D};
+use crate::support::solver::FluidSolver;
+
+const SIZE: usize = 128;
+const TIME_STEP: f32 = 0.1;
+
+/// A nozzle that keeps injecting colored ink and momentum, sweeping its direction over time.
+/// Position, radius and speed are in meters and seconds, so the nozzles stay put when the
+/// grid resolution changes.
+#[derive(Debug, Clone, Copy)]
+pub struct InkEmitter {
+    pub position: [f32; 2],
+    pub color: [f32; 3],
+    pub angle: f32,
+    pub sweep: f32,
+    pub speed: f32,
+    pub radius: f32,
+}
+
+/// Colored inks injected from the sides of a closed tank, mixing where they meet.
+pub struct InkScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub dye: RgbField2D,
+    pub emitters: Vec<InkEmitter>,
+    pub dissipation: f32,
+    pub time: f32,
+}
+
+impl InkScene {
+    pub fn new() -> Self {
+        let size = SIZE as f32;
+        let emitter = |x: f32, y: f32, color: [f32; 3], angle: f32| InkEmitter {
+            position: [x * size, y * size],
+            color,
+            angle,
+            sweep: 0.6,
+            speed: 12.0,
+            radius: 3.0,
+        };
+        let emitters = vec![
+            emitter(0.1, 0.5, [0.9, 0.1, 0.2], 0.0),
+            emitter(0.9, 0.5, [0.1, 0.3, 0.9], std::f32::consts::PI),
+            emitter(0.5, 0.9, [0.9, 0.8, 0.1], -std::f32::consts::FRAC_PI_2),
+        ];
+
+        Self {
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver: FluidSolver::new(SIZE, SIZE),
+            dye: RgbField2D::new(SIZE, SIZE, [0.0, 0.0, 0.0]),
+            emitters,
+            dissipation: 0.05,
+            time: 0.0,
+        }
+    }
+}
+
+impl Scene for InkScene {
+    fn name(&self) -> &'static str {
+        "Colored inks"
+    }
+
+    fn update(&mut self) {
+        self.time += TIME_STEP;
+
+        for (i, emitter) in self.emitters.iter().enumerate() {
+            let angle = emitter.angle + emitter.sweep * (self.time * 0.5 + i as f32 * 2.0).sin();
+            let force = [angle.cos() * emitter.speed, angle.sin() * emitter.speed];
+            let ink = emitter.color.map(|c| c * TIME_STEP * 2.0);
+
+            self.velocity_field.splat(emitter.position, emitter.radius, force.map(|f| f * TIME_STEP));
+            self.dye.splat(emitter.position, emitter.radius, ink);
+        }
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.dye = self.dye.update(&self.velocity_field, TIME_STEP);
+        self.dye.dissipate(self.dissipation, TIME_STEP);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        self.dye
+            .field
+            .rows()
+            .map(|row| row.iter().map(|color| color.map(|c| c.clamp(0.0, 1.0))).collect())
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn dye(&self) -> Option<ColorField2D> {
+        Some(self.dye.total())
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.dye = self.dye.resample(width, height);
+        true
+    }
+}
@@ -0,0 +1,133 @@
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::probe::Probe;
+use crate::support::sdf::Sdf;
+use crate::support::solver::{EdgeCondition, FluidSolver};
+use crate::support::viscosity::ViscosityModel;
+
+pub const TIME_STEP: f32 = 0.2;
+const DIAMETER: f32 = 12.0;
+const INFLOW_SPEED: f32 = 1.5;
+/// Channel size in cylinder diameters.
+const LENGTH_IN_DIAMETERS: f32 = 20.0;
+const HEIGHT_IN_DIAMETERS: f32 = 8.0;
+
+/// Probe samples kept, enough for the benchmark's measurement window of 60 D/U.
+pub const PROBE_SAMPLES: usize = 4096;
+/// Steps over which the reported Strouhal number is measured.
+const REPORT_WINDOW: usize = 1000;
+
+/// Center of the cylinder, in meters: four diameters downstream of the inlet, halfway across.
+const CYLINDER_CENTER: [f32; 2] = [4.0 * DIAMETER, 0.5 * HEIGHT_IN_DIAMETERS * DIAMETER];
+
+/// Flow past a cylinder in a channel, shedding a Kármán vortex street. A probe a few
+/// diameters downstream records the cross-stream velocity to measure the shedding frequency.
+pub struct KarmanScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub probe: Probe,
+    pub reynolds: f32,
+    pub steps: usize,
+}
+
+impl KarmanScene {
+    pub fn new(reynolds: f32) -> Self {
+        let width = (LENGTH_IN_DIAMETERS * DIAMETER) as usize;
+        let height = (HEIGHT_IN_DIAMETERS * DIAMETER) as usize;
+        let center = CYLINDER_CENTER;
+
+        let mut solver = FluidSolver::new(width, height);
+        solver.viscosity = ViscosityModel::Newtonian(INFLOW_SPEED * DIAMETER / reynolds);
+        solver.iterations = 80;
+        solver.edges = [EdgeCondition::Inflow([INFLOW_SPEED, 0.0]), EdgeCondition::Outflow, EdgeCondition::Wall, EdgeCondition::Wall];
+
+        // A small asymmetric kick so shedding starts without waiting for round-off to grow.
+        let mut velocity_field = VectorField2D::new(width, height, [INFLOW_SPEED, 0.0]);
+        velocity_field.splat([center[0] + DIAMETER, center[1]], DIAMETER * 0.5, [0.0, 0.3]);
+
+        let mut scene = Self {
+            velocity_field,
+            solver,
+            probe: Probe::new([center[0] + 3.0 * DIAMETER, center[1]], 1, TIME_STEP, PROBE_SAMPLES),
+            reynolds,
+            steps: 0,
+        };
+        scene.place_cylinder();
+        scene
+    }
+
+    /// Rasterizes the cylinder on the current cells, partly covered cells included.
+    fn place_cylinder(&mut self) {
+        self.solver.clear_obstacles();
+        self.solver.add_obstacle(&Sdf::circle(CYLINDER_CENTER, DIAMETER * 0.5).in_cells(self.velocity_field.geometry));
+        self.solver.set_boundaries(&mut self.velocity_field);
+    }
+
+    /// Strouhal number `f D / U` measured by the probe over the last `window` steps.
+    pub fn strouhal(&self, window: usize) -> Option<f32> {
+        self.probe.frequency(window).map(|frequency| frequency * DIAMETER / INFLOW_SPEED)
+    }
+
+    /// Williamson's (1988) fit for parallel shedding behind an unconfined cylinder,
+    /// valid for 49 < Re < 178.
+    pub fn expected_strouhal(&self) -> f32 {
+        -3.3265 / self.reynolds + 0.1816 + 1.6e-4 * self.reynolds
+    }
+
+    /// Time for the flow to travel one diameter, in steps.
+    pub fn steps_per_convective_time(&self) -> usize {
+        (DIAMETER / INFLOW_SPEED / TIME_STEP).round() as usize
+    }
+}
+
+impl Scene for KarmanScene {
+    fn name(&self) -> &'static str {
+        "Karman vortex street"
+    }
+
+    fn update(&mut self) {
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.probe.record(&self.velocity_field);
+        self.steps += 1;
+    }
+
+    fn report(&self) -> Option<String> {
+        let strouhal = self.strouhal(REPORT_WINDOW)?;
+        Some(format!("Re = {:.0}  St = {strouhal:.4}  (expected {:.4})", self.reynolds, self.expected_strouhal()))
+    }
+
+    fn time_step(&self) -> f32 {
+        TIME_STEP
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        (0..self.velocity_field.height())
+            .map(|y| {
+                (0..self.velocity_field.width())
+                    .map(|x| {
+                        if self.solver.is_solid(x, y) {
+                            return [0.6, 0.6, 0.65];
+                        }
+                        let vorticity = (self.velocity_field.curl(x, y) * 2.0).clamp(-1.0, 1.0);
+                        [
+                            0.1 + 0.8 * vorticity.max(0.0),
+                            0.1 + 0.2 * (1.0 - vorticity.abs()),
+                            0.1 + 0.8 * (-vorticity).max(0.0),
+                        ]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        // Redraw the cylinder on the new cells rather than keep the resampled coverage.
+        self.place_cylinder();
+        true
+    }
+}
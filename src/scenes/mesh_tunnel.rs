@@ -0,0 +1,154 @@
+use super::wind_tunnel::inject_smoke;
+use super::Scene;
+use crate::support::aerodynamics::{body_force, ForceCoefficients};
+use crate::support::field::{ColorField2D, RgbField2D, VectorField2D};
+use crate::support::grid::Grid2;
+use crate::support::mesh::{Mesh, Plane};
+use crate::support::sdf::Sdf;
+use crate::support::solver::{EdgeCondition, FluidSolver};
+use crate::support::viscosity::ViscosityModel;
+
+const WIDTH: usize = 192;
+const HEIGHT: usize = 96;
+const TIME_STEP: f32 = 0.1;
+const DENSITY: f32 = 1.0;
+/// Length of the mesh's longer side in the slice, in meters.
+const OBSTACLE_SIZE: f32 = 64.0;
+
+/// Mesh bundled with the project, a low-poly car body. Embedded in the binary so the scene
+/// does not depend on where it is run from.
+pub const CAR_MESH: &str = include_str!("../../assets/car.obj");
+
+/// Flow from the left past the side profile of an OBJ mesh, cut through its middle along `z`
+/// and resting on the floor of the tunnel.
+pub struct MeshTunnelScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    /// Slice through the mesh, in meters, rasterized again whenever the grid changes.
+    pub obstacle: Option<Sdf>,
+    pub body: Grid2<bool>,
+    pub inflow_speed: f32,
+    /// Height of the obstacle in meters, used as the reference length for the drag coefficient.
+    pub frontal_height: f32,
+    pub smoke: RgbField2D,
+    pub coefficients: ForceCoefficients,
+    pub steps: usize,
+}
+
+impl MeshTunnelScene {
+    /// The scene around the bundled car.
+    pub fn car() -> Self {
+        Self::new(&Mesh::parse(CAR_MESH).expect("the bundled car mesh is valid OBJ"))
+    }
+
+    pub fn new(mesh: &Mesh) -> Self {
+        let inflow_speed = 2.0;
+        let mut solver = FluidSolver::new(WIDTH, HEIGHT);
+        solver.viscosity = ViscosityModel::Newtonian(0.02);
+        solver.iterations = 80;
+        solver.edges = [EdgeCondition::Inflow([inflow_speed, 0.0]), EdgeCondition::Outflow, EdgeCondition::Wall, EdgeCondition::Wall];
+
+        let (min, max) = mesh.bounds();
+        let depth = 0.5 * (min[2] + max[2]);
+        let mut frontal_height = OBSTACLE_SIZE * (max[1] - min[1]) / (max[0] - min[0]).max(max[1] - min[1]);
+        let center = [WIDTH as f32 * 0.3, HEIGHT as f32 - 1.5 - frontal_height * 0.5];
+        let obstacle = mesh.slice_to_sdf(&Plane::z(depth), center, OBSTACLE_SIZE);
+        if obstacle.is_none() {
+            eprintln!("The slice through z = {depth} is empty");
+            frontal_height = 0.0;
+        }
+
+        let mut scene = Self {
+            velocity_field: VectorField2D::new(WIDTH, HEIGHT, [inflow_speed, 0.0]),
+            solver,
+            obstacle,
+            body: Grid2::new(0, 0, false),
+            inflow_speed,
+            frontal_height,
+            smoke: RgbField2D::new(WIDTH, HEIGHT, [0.0; 3]),
+            coefficients: ForceCoefficients::default(),
+            steps: 0,
+        };
+        scene.place_obstacle();
+        scene
+    }
+
+    fn place_obstacle(&mut self) {
+        self.solver.clear_obstacles();
+        if let Some(obstacle) = &self.obstacle {
+            self.solver.add_obstacle(&obstacle.in_cells(self.velocity_field.geometry));
+        }
+        self.body = self.solver.solid.clone();
+    }
+}
+
+impl Scene for MeshTunnelScene {
+    fn name(&self) -> &'static str {
+        "Wind tunnel: OBJ mesh"
+    }
+
+    fn update(&mut self) {
+        inject_smoke(&mut self.smoke);
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.smoke = self.smoke.update(&self.velocity_field, TIME_STEP);
+        self.smoke.dissipate(0.02, TIME_STEP);
+
+        if self.frontal_height > 0.0 {
+            let force = body_force(&self.solver, &self.velocity_field, &self.body, DENSITY, TIME_STEP);
+            self.coefficients = ForceCoefficients::from_force(force.total(), DENSITY, self.inflow_speed, self.frontal_height);
+        }
+
+        self.steps += 1;
+    }
+
+    fn report(&self) -> Option<String> {
+        (self.frontal_height > 0.0).then(|| format!("t = {:.1}  Cd = {:.4}", self.steps as f32 * TIME_STEP, self.coefficients.drag))
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        (0..self.solver.height)
+            .map(|y| {
+                (0..self.solver.width)
+                    .map(|x| {
+                        let [u, v] = self.velocity_field.field[y][x];
+                        let speed = ((u * u + v * v).sqrt() / (2.0 * self.inflow_speed)).min(1.0);
+                        let smoke = self.smoke.field[y][x][0].min(1.0);
+                        let background = [0.1 + 0.2 * speed, 0.15 + 0.3 * speed, 0.25 + 0.4 * speed];
+                        let fluid = self.solver.fluid_fraction.field[y][x];
+                        background.map(|c| (c + (1.0 - c) * smoke) * fluid + 0.6 * (1.0 - fluid))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn dye(&self) -> Option<ColorField2D> {
+        Some(self.smoke.total())
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.smoke = self.smoke.resample(width, height);
+        self.place_obstacle();
+        self.solver.set_boundaries(&mut self.velocity_field);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_car_loads_wherever_the_binary_runs() {
+        let scene = MeshTunnelScene::car();
+        assert!(scene.obstacle.is_some());
+        assert!(scene.frontal_height > 0.0);
+        assert!(scene.body.cells().any(|&solid| solid));
+    }
+}
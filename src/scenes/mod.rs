@@ -0,0 +1,112 @@
+//! Simulations that can be shown by the grid renderer in `main.rs`.
+use glium::winit::keyboard::KeyCode;
+
+use crate::support::field::{ColorField2D, VectorField2D};
+use crate::support::reaction_diffusion::GrayScottPreset;
+use ocean::OceanPreset;
+use shock::ShockPreset;
+
+pub mod cavity;
+pub mod droplets;
+pub mod fire;
+pub mod heat_sink;
+pub mod ink;
+pub mod karman;
+pub mod mesh_tunnel;
+pub mod nested;
+pub mod ocean;
+pub mod paint;
+pub mod patterns;
+pub mod sediment;
+pub mod shock;
+pub mod wind_tunnel;
+
+/// How the fragment shader interprets the per-cell color of a scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors are drawn as-is.
+    Rgb,
+    /// Red is temperature in `[0, 1]` and green is smoke density, mapped to a blackbody ramp.
+    Blackbody,
+}
+
+impl ColorMode {
+    pub fn uniform(self) -> i32 {
+        match self {
+            ColorMode::Rgb => 0,
+            ColorMode::Blackbody => 1,
+        }
+    }
+}
+
+/// A particle drawn on top of the grid, in cell coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    pub position: [f32; 2],
+    pub size: f32,
+    pub color: [f32; 3],
+}
+
+pub trait Scene {
+    fn name(&self) -> &'static str;
+    fn update(&mut self);
+    /// Per-cell colors, indexed `[row][col]`.
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>>;
+    fn color_mode(&self) -> ColorMode {
+        ColorMode::Rgb
+    }
+    /// Simulated time advanced by each call to `update`.
+    fn time_step(&self) -> f32 {
+        0.1
+    }
+    /// Velocity drawn by the arrow overlay, if the scene has one.
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        None
+    }
+    /// Passive scalar carried by the flow, whose total is reported by the diagnostics.
+    fn dye(&self) -> Option<ColorField2D> {
+        None
+    }
+    /// Measurements of the scene's own, printed as one line with the diagnostics.
+    fn report(&self) -> Option<String> {
+        None
+    }
+    /// Particles simulated by the scene itself, drawn as small crosses.
+    fn markers(&self) -> Vec<Marker> {
+        Vec::new()
+    }
+    /// Changes the grid to `width` x `height` cells without restarting, carrying the current
+    /// state over. Returns `false` if the scene only runs at its built-in resolution.
+    fn resample(&mut self, _width: usize, _height: usize) -> bool {
+        false
+    }
+    /// Keys not used by the application itself.
+    fn handle_key(&mut self, _key: KeyCode) {}
+}
+
+/// Every selectable scene, in the order they are cycled through with Tab.
+pub const SCENES: &[fn() -> Box<dyn Scene>] = &[
+    || Box::new(ink::InkScene::new()),
+    || Box::new(fire::FireScene::candle()),
+    || Box::new(fire::FireScene::fireball()),
+    || Box::new(patterns::PatternScene::new(GrayScottPreset::Coral)),
+    || Box::new(patterns::PatternScene::new(GrayScottPreset::Mitosis)),
+    || Box::new(patterns::PatternScene::new(GrayScottPreset::Worms)),
+    || Box::new(droplets::DropletScene::new()),
+    || Box::new(paint::PaintScene::bingham()),
+    || Box::new(paint::PaintScene::carreau()),
+    || Box::new(paint::PaintScene::power_law()),
+    || Box::new(paint::PaintScene::newtonian()),
+    || Box::new(ocean::OceanScene::new(OceanPreset::Tsunami)),
+    || Box::new(ocean::OceanScene::new(OceanPreset::RotatingBasin)),
+    || Box::new(heat_sink::HeatSinkScene::new()),
+    || Box::new(sediment::SedimentScene::new()),
+    || Box::new(wind_tunnel::WindTunnelScene::new()),
+    || Box::new(mesh_tunnel::MeshTunnelScene::car()),
+    || Box::new(karman::KarmanScene::new(100.0)),
+    || Box::new(nested::NestedKarmanScene::new(100.0)),
+    || Box::new(cavity::LidDrivenCavityScene::new(400.0, 96)),
+    || Box::new(cavity::LidDrivenCavityScene::turbulent(10000.0, 128)),
+    || Box::new(shock::ShockScene::new(ShockPreset::SodShockTube)),
+    || Box::new(shock::ShockScene::new(ShockPreset::RiemannProblem)),
+];
@@ -0,0 +1,159 @@
+use super::karman::PROBE_SAMPLES;
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::grid::GridGeometry;
+use crate::support::nested::NestedGrid;
+use crate::support::probe::Probe;
+use crate::support::sdf::Sdf;
+use crate::support::solver::{EdgeCondition, FluidSolver};
+use crate::support::viscosity::ViscosityModel;
+
+const TIME_STEP: f32 = 0.2;
+/// Cylinder diameter, in meters.
+const DIAMETER: f32 = 12.0;
+const INFLOW_SPEED: f32 = 1.5;
+/// Cell size of the coarse grid, in meters.
+const COARSE_CELL: f32 = 2.0;
+const COARSE_SIZE: [usize; 2] = [120, 48];
+/// Coarse cells covered by the fine patch around the cylinder, and how finely it splits them.
+const PATCH_OFFSET: [usize; 2] = [14, 12];
+const PATCH_SIZE: [usize; 2] = [40, 24];
+const PATCH_RATIO: usize = 2;
+/// Steps over which the reported Strouhal number is measured.
+const REPORT_WINDOW: usize = 1000;
+
+/// The Kármán vortex street of `KarmanScene` on a coarse channel with a fine patch around
+/// the cylinder, which gets the same resolution as the uniform scene there for well under
+/// half the cells. The patch outline is drawn in the display.
+pub struct NestedKarmanScene {
+    pub grid: NestedGrid,
+    pub probe: Probe,
+    pub reynolds: f32,
+    pub steps: usize,
+}
+
+impl NestedKarmanScene {
+    pub fn new(reynolds: f32) -> Self {
+        Self::with_scale(reynolds, 1.0).expect("the built-in patch fits its channel")
+    }
+
+    /// The scene with `scale` times as many cells along each side on every level, or `None`
+    /// if the grid is too coarse to hold the fine patch inside the channel.
+    pub fn with_scale(reynolds: f32, scale: f32) -> Option<Self> {
+        let scaled = |cells: [usize; 2]| cells.map(|c| ((c as f32 * scale).round() as usize).max(1));
+        let [width, height] = scaled(COARSE_SIZE);
+        let (offset, size) = (scaled(PATCH_OFFSET), scaled(PATCH_SIZE));
+        if offset[0] + size[0] >= width || offset[1] + size[1] >= height {
+            return None;
+        }
+        let coarse_cell = COARSE_CELL / scale;
+        let center = [4.0 * DIAMETER, COARSE_SIZE[1] as f32 * 0.5 * COARSE_CELL];
+
+        let mut solver = FluidSolver::new(width, height);
+        solver.viscosity = ViscosityModel::Newtonian(INFLOW_SPEED * DIAMETER / reynolds);
+        solver.iterations = 80;
+        solver.edges = [EdgeCondition::Inflow([INFLOW_SPEED, 0.0]), EdgeCondition::Outflow, EdgeCondition::Wall, EdgeCondition::Wall];
+
+        // A small asymmetric kick so shedding starts without waiting for round-off to grow.
+        let geometry = GridGeometry::uniform(coarse_cell);
+        let mut velocity_field = VectorField2D::new(width, height, [INFLOW_SPEED, 0.0]).with_geometry(geometry);
+        velocity_field.splat([center[0] + DIAMETER, center[1]], DIAMETER * 0.5, [0.0, 0.3]);
+
+        let mut grid = NestedGrid::new(solver, velocity_field);
+        grid.refine(offset, size, PATCH_RATIO);
+        grid.add_obstacle(Sdf::circle(center, DIAMETER * 0.5));
+
+        Some(Self {
+            grid,
+            probe: Probe::new([center[0] + 3.0 * DIAMETER, center[1]], 1, TIME_STEP, PROBE_SAMPLES),
+            reynolds,
+            steps: 0,
+        })
+    }
+
+    /// Strouhal number `f D / U` measured by the probe over the last `window` steps.
+    pub fn strouhal(&self, window: usize) -> Option<f32> {
+        self.probe.frequency(window).map(|frequency| frequency * DIAMETER / INFLOW_SPEED)
+    }
+
+    /// Cells of all levels together.
+    pub fn cells(&self) -> usize {
+        self.grid.levels.iter().map(|level| level.solver.width * level.solver.height).sum()
+    }
+}
+
+impl Scene for NestedKarmanScene {
+    fn name(&self) -> &'static str {
+        "Karman vortex street (nested grid)"
+    }
+
+    fn update(&mut self) {
+        self.grid.step(TIME_STEP);
+        self.probe.record(&self.grid.finest().velocity_field);
+        self.steps += 1;
+    }
+
+    fn report(&self) -> Option<String> {
+        let strouhal = self.strouhal(REPORT_WINDOW)?;
+        Some(format!("Re = {:.0}  St = {strouhal:.4}  on {} cells", self.reynolds, self.cells()))
+    }
+
+    fn time_step(&self) -> f32 {
+        TIME_STEP
+    }
+
+    /// The channel at the resolution of the fine patch, each pixel taken from the finest
+    /// level covering it.
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        let coarsest = self.grid.coarsest();
+        let coarse_cell = coarsest.geometry().spacing[0];
+        let pixel = coarse_cell / PATCH_RATIO as f32;
+        let geometry = GridGeometry::new([0.5 * (pixel - coarse_cell); 2], [pixel; 2]);
+        let finest = self.grid.finest();
+        (0..coarsest.solver.height * PATCH_RATIO)
+            .map(|y| {
+                (0..coarsest.solver.width * PATCH_RATIO)
+                    .map(|x| {
+                        let position = geometry.position(x as f32, y as f32);
+                        let (level, cx, cy) = self.grid.level_at(position);
+                        if level.solver.is_solid(cx, cy) {
+                            return [0.6, 0.6, 0.65];
+                        }
+                        if finest.cell(position).is_some_and(|(fx, fy)| fx == 1 || fy == 1 || fx == finest.solver.width - 2 || fy == finest.solver.height - 2) {
+                            return [0.35, 0.35, 0.35];
+                        }
+                        let vorticity = (level.velocity_field.curl(cx, cy) * 2.0).clamp(-1.0, 1.0);
+                        [
+                            0.1 + 0.8 * vorticity.max(0.0),
+                            0.1 + 0.2 * (1.0 - vorticity.abs()),
+                            0.1 + 0.8 * (-vorticity).max(0.0),
+                        ]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Rebuilds the nesting with every level scaled to match the new display size, which
+    /// shows the channel at the resolution of the fine patch, and carries the flow over by
+    /// sampling the old levels.
+    fn resample(&mut self, width: usize, _height: usize) -> bool {
+        let scale = width as f32 / (COARSE_SIZE[0] * PATCH_RATIO) as f32;
+        let Some(mut resampled) = Self::with_scale(self.reynolds, scale) else {
+            return false;
+        };
+        for level in &mut resampled.grid.levels {
+            let geometry = level.geometry();
+            for (y, row) in level.velocity_field.field.rows_mut().enumerate() {
+                for (x, value) in row.iter_mut().enumerate() {
+                    *value = self.grid.sample(geometry.position(x as f32, y as f32));
+                }
+            }
+            level.solver.set_boundaries(&mut level.velocity_field);
+        }
+        resampled.probe = self.probe.clone();
+        resampled.steps = self.steps;
+        *self = resampled;
+        true
+    }
+}
@@ -0,0 +1,118 @@
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::shallow_water::ShallowWater;
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 96;
+const TIME_STEP: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OceanPreset {
+    /// A raised patch of sea in deep water running up a shelf onto a dry beach.
+    Tsunami,
+    /// A dome of water in a rotating basin, adjusting into a geostrophic eddy.
+    RotatingBasin,
+}
+
+pub struct OceanScene {
+    pub preset: OceanPreset,
+    pub water: ShallowWater,
+    pub velocity_field: VectorField2D,
+    pub sea_level: f32,
+}
+
+impl OceanScene {
+    pub fn new(preset: OceanPreset) -> Self {
+        let mut water = ShallowWater::new(WIDTH, HEIGHT);
+        let sea_level = 0.0;
+
+        match preset {
+            OceanPreset::Tsunami => {
+                // Deep ocean on the left, a continental shelf and then a beach rising above sea level.
+                for row in water.bathymetry.field.rows_mut() {
+                    for (x, bed) in row.iter_mut().enumerate() {
+                        let s = x as f32 / WIDTH as f32;
+                        *bed = if s < 0.5 { -2.0 } else { -2.0 + (s - 0.5) * 5.0 };
+                    }
+                }
+                for (y, row) in water.bathymetry.field.rows_mut().enumerate() {
+                    let island = ((y as f32 - HEIGHT as f32 * 0.3) / 6.0).powi(2);
+                    for (x, bed) in row.iter_mut().enumerate() {
+                        let bump = 2.5 * (-((x as f32 - WIDTH as f32 * 0.55) / 6.0).powi(2) - island).exp();
+                        *bed += bump;
+                    }
+                }
+                water.fill_to_level(sea_level);
+                water.add_bump([WIDTH as f32 * 0.15, HEIGHT as f32 * 0.5], 8.0, 0.8);
+            }
+            OceanPreset::RotatingBasin => {
+                for (y, row) in water.bathymetry.field.rows_mut().enumerate() {
+                    for (x, bed) in row.iter_mut().enumerate() {
+                        let dx = (x as f32 / WIDTH as f32 - 0.5) * 2.0;
+                        let dy = (y as f32 / HEIGHT as f32 - 0.5) * 2.0;
+                        *bed = -1.0 + 0.8 * (dx * dx + dy * dy);
+                    }
+                }
+                water.coriolis = Some(0.3);
+                water.fill_to_level(sea_level);
+                water.add_bump([WIDTH as f32 * 0.5, HEIGHT as f32 * 0.5], 10.0, 0.3);
+            }
+        }
+
+        let velocity_field = water.velocity_field();
+        Self {
+            preset,
+            water,
+            velocity_field,
+            sea_level,
+        }
+    }
+}
+
+impl Scene for OceanScene {
+    fn name(&self) -> &'static str {
+        match self.preset {
+            OceanPreset::Tsunami => "Shallow water: tsunami",
+            OceanPreset::RotatingBasin => "Shallow water: rotating basin",
+        }
+    }
+
+    fn update(&mut self) {
+        self.water.advance(TIME_STEP);
+        self.velocity_field = self.water.velocity_field();
+    }
+
+    fn time_step(&self) -> f32 {
+        TIME_STEP
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        let surface = self.water.surface_elevation();
+
+        (0..HEIGHT)
+            .map(|y| {
+                (0..WIDTH)
+                    .map(|x| {
+                        let depth = self.water.depth.field[y][x];
+                        let bed = self.water.bathymetry.field[y][x];
+                        if depth <= self.water.dry_tolerance {
+                            let height = (bed - self.sea_level).clamp(0.0, 1.0);
+                            return [0.55 - 0.2 * height, 0.45 + 0.1 * height, 0.25];
+                        }
+                        let wave = ((surface.field[y][x] - self.sea_level) * 2.0).clamp(-1.0, 1.0);
+                        let shade = (1.0 - depth / 3.0).clamp(0.0, 1.0);
+                        [
+                            0.05 + 0.1 * shade + 0.6 * wave.max(0.0),
+                            0.2 + 0.3 * shade + 0.6 * wave.max(0.0),
+                            0.45 + 0.3 * shade + 0.4 * wave,
+                        ]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+}
@@ -0,0 +1,116 @@
+use super::Scene;
+use crate::support::field::{ColorField2D, RgbField2D, VectorField2D};
+use crate::support::solver::FluidSolver;
+use crate::support::viscosity::ViscosityModel;
+
+const SIZE: usize = 96;
+const TIME_STEP: f32 = 0.1;
+const STRIPE_COLORS: [[f32; 3]; 4] = [
+    [0.85, 0.2, 0.15],
+    [0.95, 0.8, 0.2],
+    [0.15, 0.45, 0.8],
+    [0.9, 0.9, 0.85],
+];
+
+/// Striped paint stirred by a paddle circling the middle of a closed pot. With a yield
+/// stress or shear-thinning model only the paint near the paddle flows.
+pub struct PaintScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub dye: RgbField2D,
+    pub paddle_speed: f32,
+    pub time: f32,
+}
+
+impl PaintScene {
+    pub fn new(viscosity: ViscosityModel) -> Self {
+        let mut dye = RgbField2D::new(SIZE, SIZE, [0.0; 3]);
+        for (y, row) in dye.field.rows_mut().enumerate() {
+            row.fill(STRIPE_COLORS[(y / 8) % STRIPE_COLORS.len()]);
+        }
+
+        let mut solver = FluidSolver::new(SIZE, SIZE);
+        solver.viscosity = viscosity;
+
+        Self {
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver,
+            dye,
+            paddle_speed: 3.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn bingham() -> Self {
+        Self::new(ViscosityModel::Bingham { plastic_viscosity: 0.1, yield_stress: 1.0, regularization: 50.0 })
+    }
+
+    pub fn carreau() -> Self {
+        Self::new(ViscosityModel::Carreau { zero_shear: 20.0, infinite_shear: 0.05, relaxation_time: 10.0, flow_index: 0.3 })
+    }
+
+    /// Shear-thinning paint without a yield stress or a viscosity plateau.
+    pub fn power_law() -> Self {
+        Self::new(ViscosityModel::PowerLaw { consistency: 0.5, flow_index: 0.5 })
+    }
+
+    pub fn newtonian() -> Self {
+        Self::new(ViscosityModel::Newtonian(0.1))
+    }
+}
+
+impl Scene for PaintScene {
+    fn name(&self) -> &'static str {
+        match self.solver.viscosity {
+            ViscosityModel::Newtonian(_) => "Paint: Newtonian",
+            ViscosityModel::PowerLaw { .. } => "Paint: power law",
+            ViscosityModel::Bingham { .. } => "Paint: Bingham plastic",
+            ViscosityModel::Carreau { .. } => "Paint: Carreau slurry",
+        }
+    }
+
+    fn update(&mut self) {
+        self.time += TIME_STEP;
+
+        let center = SIZE as f32 * 0.5;
+        let orbit = SIZE as f32 * 0.2;
+        let angular_speed = self.paddle_speed / orbit;
+        let angle = self.time * angular_speed;
+        let paddle = (center + orbit * angle.cos(), center + orbit * angle.sin());
+
+        // The paddle drags the paint under it towards its own velocity.
+        let paddle_velocity = [-angle.sin() * self.paddle_speed, angle.cos() * self.paddle_speed];
+        let radius = 4.0;
+        let geometry = self.velocity_field.geometry;
+        for (y, row) in self.velocity_field.field.rows_mut().enumerate() {
+            for (x, velocity) in row.iter_mut().enumerate() {
+                let [px, py] = geometry.position(x as f32, y as f32);
+                let distance = ((px - paddle.0).powi(2) + (py - paddle.1).powi(2)).sqrt();
+                if distance < radius {
+                    *velocity = paddle_velocity;
+                }
+            }
+        }
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.dye = self.dye.update(&self.velocity_field, TIME_STEP);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        self.dye.field.to_rows()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn dye(&self) -> Option<ColorField2D> {
+        Some(self.dye.total())
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.dye = self.dye.resample(width, height);
+        true
+    }
+}
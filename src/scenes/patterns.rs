@@ -0,0 +1,94 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::Scene;
+use crate::support::field::VectorField2D;
+use crate::support::reaction_diffusion::{GrayScott, GrayScottPreset, ReactionDiffusion};
+use crate::support::solver::FluidSolver;
+
+const SIZE: usize = 128;
+const TIME_STEP: f32 = 0.1;
+
+/// Gray–Scott patterns growing while a slowly orbiting stirrer drags them around.
+pub struct PatternScene {
+    pub preset: GrayScottPreset,
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub chemistry: ReactionDiffusion<GrayScott>,
+    pub stir_strength: f32,
+    pub time: f32,
+}
+
+impl PatternScene {
+    pub fn new(preset: GrayScottPreset) -> Self {
+        let mut chemistry = ReactionDiffusion::new(SIZE, SIZE, GrayScott::preset(preset), (1.0, 0.0));
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..12 {
+            let x = rng.gen_range(0.2..0.8) * SIZE as f32;
+            let y = rng.gen_range(0.2..0.8) * SIZE as f32;
+            chemistry.seed([x, y], 3.0, 0.5, 0.25);
+        }
+
+        Self {
+            preset,
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver: FluidSolver::new(SIZE, SIZE),
+            chemistry,
+            stir_strength: 0.4,
+            time: 0.0,
+        }
+    }
+}
+
+impl Scene for PatternScene {
+    fn name(&self) -> &'static str {
+        match self.preset {
+            GrayScottPreset::Coral => "Gray-Scott: coral",
+            GrayScottPreset::Mitosis => "Gray-Scott: mitosis",
+            GrayScottPreset::Fingerprint => "Gray-Scott: fingerprint",
+            GrayScottPreset::Spots => "Gray-Scott: spots",
+            GrayScottPreset::Worms => "Gray-Scott: worms",
+        }
+    }
+
+    fn update(&mut self) {
+        self.time += TIME_STEP;
+
+        let center = SIZE as f32 * 0.5;
+        let orbit = SIZE as f32 * 0.25;
+        let angle = self.time * 0.05;
+        let (x, y) = (center + orbit * angle.cos(), center + orbit * angle.sin());
+        let force = [-angle.sin() * self.stir_strength, angle.cos() * self.stir_strength];
+        self.velocity_field.splat([x, y], 10.0, force.map(|f| f * TIME_STEP));
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.chemistry.update(Some(&self.velocity_field), TIME_STEP, 1.0);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        self.chemistry
+            .b
+            .field
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&b| {
+                        let t = (b * 3.0).clamp(0.0, 1.0);
+                        [0.05 + 0.9 * t * t, 0.1 + 0.8 * t, 0.25 + 0.6 * t.sqrt()]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.chemistry.resample(width, height);
+        true
+    }
+}
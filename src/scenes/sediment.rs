@@ -0,0 +1,117 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{Marker, Scene};
+use crate::support::field::VectorField2D;
+use crate::support::particles::InertialParticleSystem;
+use crate::support::solver::FluidSolver;
+use crate::support::viscosity::ViscosityModel;
+
+const SIZE: usize = 96;
+const TIME_STEP: f32 = 0.1;
+const SAND_DENSITY: f32 = 2.6;
+const DUST_DENSITY: f32 = 1.2;
+
+/// Sand and dust in a tank stirred by an orbiting impeller, with a baffle on one wall.
+/// Fine dust stays suspended and follows the eddies, while sand lags behind and settles.
+pub struct SedimentScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub particles: InertialParticleSystem,
+    pub stir_strength: f32,
+    pub time: f32,
+}
+
+impl SedimentScene {
+    pub fn new() -> Self {
+        let mut solver = FluidSolver::new(SIZE, SIZE);
+        solver.viscosity = ViscosityModel::Newtonian(0.01);
+        solver.add_solid_rectangle(70, 40, 95, 44);
+
+        let mut particles = InertialParticleSystem::new();
+        particles.fluid_viscosity = 0.01;
+        let mut rng = StdRng::seed_from_u64(3);
+        for i in 0..600 {
+            let position = [rng.gen_range(5.0..60.0), rng.gen_range(60.0..90.0)];
+            if i % 2 == 0 {
+                particles.add(position, rng.gen_range(0.07..0.11), SAND_DENSITY);
+            } else {
+                particles.add(position, rng.gen_range(0.02..0.05), DUST_DENSITY);
+            }
+        }
+
+        Self {
+            velocity_field: VectorField2D::new(SIZE, SIZE, [0.0, 0.0]),
+            solver,
+            particles,
+            stir_strength: 3.0,
+            time: 0.0,
+        }
+    }
+}
+
+impl Scene for SedimentScene {
+    fn name(&self) -> &'static str {
+        "Sediment and dust in a stirred tank"
+    }
+
+    fn update(&mut self) {
+        self.time += TIME_STEP;
+
+        let center = SIZE as f32 * 0.5;
+        let orbit = SIZE as f32 * 0.25;
+        let angle = self.time * 0.3;
+        let (x, y) = (center + orbit * angle.cos(), center + orbit * angle.sin());
+        let force = [-angle.sin() * self.stir_strength, angle.cos() * self.stir_strength];
+        self.velocity_field.splat([x, y], 8.0, force.map(|f| f * TIME_STEP));
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.particles.update(&self.velocity_field, Some(&self.solver.solid), TIME_STEP);
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        self.velocity_field
+            .field
+            .rows()
+            .zip(self.solver.solid.rows())
+            .map(|(velocities, solids)| {
+                velocities
+                    .iter()
+                    .zip(solids)
+                    .map(|(velocity, &solid)| {
+                        if solid {
+                            return [0.5, 0.5, 0.55];
+                        }
+                        let speed = (velocity[0].hypot(velocity[1]) / 4.0).min(1.0);
+                        [0.02, 0.08 + 0.15 * speed, 0.15 + 0.3 * speed]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        true
+    }
+
+    fn markers(&self) -> Vec<Marker> {
+        let geometry = self.velocity_field.geometry;
+        self.particles
+            .particles
+            .iter()
+            .map(|particle| {
+                let (size, color) = if particle.density >= SAND_DENSITY {
+                    (1.0, [0.9, 0.75, 0.4])
+                } else {
+                    (0.5, [0.8, 0.8, 0.8])
+                };
+                Marker { position: geometry.index(particle.position), size, color }
+            })
+            .collect()
+    }
+}
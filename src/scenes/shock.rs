@@ -0,0 +1,110 @@
+use super::Scene;
+use crate::support::euler::CompressibleEuler;
+use crate::support::field::VectorField2D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShockPreset {
+    /// Sod's shock tube: high-pressure gas on the left expands into low-pressure gas, giving a
+    /// rarefaction, a contact discontinuity and a shock running to the right.
+    SodShockTube,
+    /// Four constant quadrants meeting at the center (Lax & Liu configuration 3), which
+    /// interact into curved shocks and a mushroom-shaped jet along the diagonal.
+    RiemannProblem,
+}
+
+/// Supersonic gas dynamics on the unit square (or a unit-length tube), drawn as density.
+/// The simulation stops at the time the reference solutions are usually shown at.
+pub struct ShockScene {
+    pub preset: ShockPreset,
+    pub gas: CompressibleEuler,
+    pub velocity_field: VectorField2D,
+    pub time: f32,
+    pub end_time: f32,
+    /// Density range mapped onto the color ramp.
+    pub density_range: (f32, f32),
+}
+
+impl ShockScene {
+    pub fn new(preset: ShockPreset) -> Self {
+        let (mut gas, end_time, density_range) = match preset {
+            ShockPreset::SodShockTube => {
+                let mut gas = CompressibleEuler::new(256, 24, 1.0 / 256.0);
+                gas.initialize(|x, _| if x < 0.5 { (1.0, [0.0, 0.0], 1.0) } else { (0.125, [0.0, 0.0], 0.1) });
+                (gas, 0.2, (0.1, 1.0))
+            }
+            ShockPreset::RiemannProblem => {
+                let mut gas = CompressibleEuler::new(192, 192, 1.0 / 192.0);
+                // Rows grow downwards, so the upper quadrants are at y < 0.5 and an upward
+                // velocity is negative.
+                gas.initialize(|x, y| match (x >= 0.5, y < 0.5) {
+                    (true, true) => (1.5, [0.0, 0.0], 1.5),
+                    (false, true) => (0.5323, [1.206, 0.0], 0.3),
+                    (false, false) => (0.138, [1.206, -1.206], 0.029),
+                    (true, false) => (0.5323, [0.0, -1.206], 0.3),
+                });
+                (gas, 0.3, (0.1, 1.8))
+            }
+        };
+        gas.courant = 0.4;
+
+        Self {
+            preset,
+            velocity_field: gas.velocity_field(),
+            gas,
+            time: 0.0,
+            end_time,
+            density_range,
+        }
+    }
+
+    fn frame_time(&self) -> f32 {
+        self.end_time / 150.0
+    }
+}
+
+impl Scene for ShockScene {
+    fn name(&self) -> &'static str {
+        match self.preset {
+            ShockPreset::SodShockTube => "Compressible gas: Sod shock tube",
+            ShockPreset::RiemannProblem => "Compressible gas: 2D Riemann problem",
+        }
+    }
+
+    fn update(&mut self) {
+        if self.time >= self.end_time {
+            return;
+        }
+        let delta_time = self.frame_time().min(self.end_time - self.time);
+        self.gas.advance(delta_time);
+        self.time += delta_time;
+        self.velocity_field = self.gas.velocity_field();
+    }
+
+    fn time_step(&self) -> f32 {
+        self.frame_time()
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        let (low, high) = self.density_range;
+        self.gas
+            .density
+            .field
+            .rows()
+            .map(|row| row.iter().map(|&density| density_color(((density - low) / (high - low)).clamp(0.0, 1.0))).collect())
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+}
+
+/// Dark blue through teal and yellow to white, for `t` in `[0, 1]`.
+fn density_color(t: f32) -> [f32; 3] {
+    const STOPS: [[f32; 3]; 4] = [[0.05, 0.05, 0.25], [0.1, 0.55, 0.6], [0.95, 0.85, 0.25], [1.0, 1.0, 1.0]];
+    let position = t * (STOPS.len() - 1) as f32;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let blend = position - index as f32;
+    let (a, b) = (STOPS[index], STOPS[index + 1]);
+    [a[0] + (b[0] - a[0]) * blend, a[1] + (b[1] - a[1]) * blend, a[2] + (b[2] - a[2]) * blend]
+}
@@ -0,0 +1,157 @@
+use glium::winit::keyboard::KeyCode;
+
+use super::Scene;
+use crate::support::aerodynamics::{body_force, Airfoil, ForceCoefficients, Naca4};
+use crate::support::field::{ColorField2D, RgbField2D, VectorField2D};
+use crate::support::grid::Grid2;
+use crate::support::sdf::Sdf;
+use crate::support::solver::{EdgeCondition, FluidSolver};
+use crate::support::viscosity::ViscosityModel;
+
+const WIDTH: usize = 192;
+const HEIGHT: usize = 96;
+const TIME_STEP: f32 = 0.1;
+const DENSITY: f32 = 1.0;
+
+/// Uniform flow from the left past a NACA airfoil, with smoke streaks injected at the
+/// inlet. Drag and lift coefficients are computed every step; Up and Down change the
+/// angle of attack.
+pub struct WindTunnelScene {
+    pub velocity_field: VectorField2D,
+    pub solver: FluidSolver,
+    pub airfoil: Airfoil,
+    pub body: Grid2<bool>,
+    pub inflow_speed: f32,
+    pub smoke: RgbField2D,
+    pub coefficients: ForceCoefficients,
+    pub steps: usize,
+}
+
+impl WindTunnelScene {
+    pub fn new() -> Self {
+        let inflow_speed = 2.0;
+        let mut solver = FluidSolver::new(WIDTH, HEIGHT);
+        solver.viscosity = ViscosityModel::Newtonian(0.02);
+        solver.iterations = 80;
+        solver.edges = [EdgeCondition::Inflow([inflow_speed, 0.0]), EdgeCondition::Outflow, EdgeCondition::Wall, EdgeCondition::Wall];
+
+        let airfoil = Airfoil {
+            section: Naca4::new(2, 4, 12),
+            position: [WIDTH as f32 * 0.3, HEIGHT as f32 * 0.5],
+            chord: 48.0,
+            angle_of_attack: 5.0,
+        };
+
+        let mut scene = Self {
+            velocity_field: VectorField2D::new(WIDTH, HEIGHT, [inflow_speed, 0.0]),
+            solver,
+            airfoil,
+            body: Grid2::new(0, 0, false),
+            inflow_speed,
+            smoke: RgbField2D::new(WIDTH, HEIGHT, [0.0; 3]),
+            coefficients: ForceCoefficients::default(),
+            steps: 0,
+        };
+        scene.place_airfoil();
+        scene
+    }
+
+    fn place_airfoil(&mut self) {
+        self.solver.clear_obstacles();
+        self.solver.add_obstacle(&Sdf::polygon(self.airfoil.polygon()).in_cells(self.velocity_field.geometry));
+        self.body = self.solver.solid.clone();
+    }
+}
+
+impl Scene for WindTunnelScene {
+    fn name(&self) -> &'static str {
+        "Wind tunnel: NACA 2412"
+    }
+
+    fn update(&mut self) {
+        inject_smoke(&mut self.smoke);
+
+        self.solver.step(&mut self.velocity_field, TIME_STEP);
+        self.smoke = self.smoke.update(&self.velocity_field, TIME_STEP);
+        self.smoke.dissipate(0.02, TIME_STEP);
+
+        let force = body_force(&self.solver, &self.velocity_field, &self.body, DENSITY, TIME_STEP);
+        self.coefficients = ForceCoefficients::from_force(force.total(), DENSITY, self.inflow_speed, self.airfoil.chord);
+
+        self.steps += 1;
+    }
+
+    fn report(&self) -> Option<String> {
+        Some(format!(
+            "t = {:.1}  alpha = {:.1}  Cd = {:.4}  Cl = {:.4}",
+            self.steps as f32 * TIME_STEP,
+            self.airfoil.angle_of_attack,
+            self.coefficients.drag,
+            self.coefficients.lift
+        ))
+    }
+
+    fn color_matrix(&self) -> Vec<Vec<[f32; 3]>> {
+        let dynamic_pressure = 0.5 * DENSITY * self.inflow_speed * self.inflow_speed;
+
+        (0..self.solver.height)
+            .map(|y| {
+                (0..self.solver.width)
+                    .map(|x| {
+                        if self.body[y][x] {
+                            return [0.6, 0.6, 0.65];
+                        }
+                        let pressure = DENSITY * self.solver.pressure.field[y][x] / TIME_STEP;
+                        let coefficient = (pressure / dynamic_pressure).clamp(-1.0, 1.0);
+                        let smoke = self.smoke.field[y][x][0].min(1.0);
+                        let background = [
+                            0.15 + 0.35 * coefficient.max(0.0),
+                            0.15,
+                            0.15 + 0.35 * (-coefficient).max(0.0),
+                        ];
+                        let fluid = self.solver.fluid_fraction.field[y][x];
+                        background.map(|c| (c + (1.0 - c) * smoke) * fluid + 0.6 * (1.0 - fluid))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn velocity_field(&self) -> Option<&VectorField2D> {
+        Some(&self.velocity_field)
+    }
+
+    fn dye(&self) -> Option<ColorField2D> {
+        Some(self.smoke.total())
+    }
+
+    fn resample(&mut self, width: usize, height: usize) -> bool {
+        self.solver.resample(&mut self.velocity_field, width, height);
+        self.smoke = self.smoke.resample(width, height);
+        self.place_airfoil();
+        true
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::ArrowUp => self.airfoil.angle_of_attack += 1.0,
+            KeyCode::ArrowDown => self.airfoil.angle_of_attack -= 1.0,
+            _ => return,
+        }
+        self.place_airfoil();
+        println!("Angle of attack: {:.1} degrees", self.airfoil.angle_of_attack);
+    }
+}
+
+/// Feeds the smoke streaks at the inlet, one every six meters across the tunnel whatever
+/// the grid resolution.
+pub(crate) fn inject_smoke(smoke: &mut RgbField2D) {
+    let geometry = smoke.geometry;
+    let bottom = geometry.position(0.0, (smoke.height() - 1) as f32)[1];
+    let mut streak = geometry.origin[1] + 4.0;
+    while streak <= bottom - 4.0 {
+        let y = geometry.index([0.0, streak])[1].round() as usize;
+        smoke.field[y][1] = [0.9, 0.9, 0.9];
+        streak += 6.0;
+    }
+}
@@ -0,0 +1,239 @@
+use super::field::VectorField2D;
+use super::grid::Grid2;
+use super::solver::FluidSolver;
+
+/// NACA 4-digit airfoil section, e.g. `Naca4::new(2, 4, 12)` for a NACA 2412.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Naca4 {
+    /// Maximum camber, as a fraction of the chord.
+    pub camber: f32,
+    /// Position of the maximum camber, as a fraction of the chord.
+    pub camber_position: f32,
+    /// Maximum thickness, as a fraction of the chord.
+    pub thickness: f32,
+}
+
+impl Naca4 {
+    pub fn new(camber: u32, camber_position: u32, thickness: u32) -> Self {
+        Self {
+            camber: camber as f32 / 100.0,
+            camber_position: camber_position as f32 / 10.0,
+            thickness: thickness as f32 / 100.0,
+        }
+    }
+
+    /// Parses a designation like `"0012"` or `"NACA 2412"`.
+    pub fn parse(designation: &str) -> Option<Self> {
+        let digits: Vec<u32> = designation.chars().filter_map(|c| c.to_digit(10)).collect();
+        match digits[..] {
+            [m, p, t1, t2] => Some(Self::new(m, p, t1 * 10 + t2)),
+            _ => None,
+        }
+    }
+
+    /// Half thickness at `x` along a unit chord, with a closed trailing edge.
+    fn thickness_at(&self, x: f32) -> f32 {
+        5.0 * self.thickness * (0.2969 * x.sqrt() - 0.1260 * x - 0.3516 * x * x + 0.2843 * x.powi(3) - 0.1036 * x.powi(4))
+    }
+
+    /// Camber line height and slope at `x` along a unit chord.
+    fn camber_at(&self, x: f32) -> (f32, f32) {
+        let (m, p) = (self.camber, self.camber_position);
+        if m == 0.0 || p == 0.0 {
+            (0.0, 0.0)
+        } else if x < p {
+            (m / (p * p) * (2.0 * p * x - x * x), 2.0 * m / (p * p) * (p - x))
+        } else {
+            let q = (1.0 - p) * (1.0 - p);
+            (m / q * (1.0 - 2.0 * p + 2.0 * p * x - x * x), 2.0 * m / q * (p - x))
+        }
+    }
+
+    /// Outline on a unit chord from the trailing edge over the upper surface to the
+    /// leading edge and back along the lower surface, with `y` pointing up.
+    pub fn outline(&self, points_per_side: usize) -> Vec<[f32; 2]> {
+        let surface = |i: usize, upper: bool| {
+            // Cosine spacing puts more points near the leading edge.
+            let x = 0.5 * (1.0 - (std::f32::consts::PI * i as f32 / points_per_side as f32).cos());
+            let (yc, slope) = self.camber_at(x);
+            let yt = self.thickness_at(x);
+            let theta = slope.atan();
+            let sign = if upper { 1.0 } else { -1.0 };
+            [x - sign * yt * theta.sin(), yc + sign * yt * theta.cos()]
+        };
+
+        let mut outline: Vec<[f32; 2]> = (0..=points_per_side).rev().map(|i| surface(i, true)).collect();
+        outline.extend((1..points_per_side).map(|i| surface(i, false)));
+        outline
+    }
+}
+
+/// An airfoil placed in the domain. The section is rotated about its quarter chord, with a
+/// positive angle of attack raising the leading edge for a flow coming from the left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Airfoil {
+    pub section: Naca4,
+    /// Position of the quarter-chord point, in meters.
+    pub position: [f32; 2],
+    /// Chord length, in meters.
+    pub chord: f32,
+    /// Angle of attack, in degrees.
+    pub angle_of_attack: f32,
+}
+
+impl Airfoil {
+    /// Outline in meters, with `y` growing downwards like the rows of the grid.
+    pub fn polygon(&self) -> Vec<[f32; 2]> {
+        let (sin, cos) = self.angle_of_attack.to_radians().sin_cos();
+        self.section
+            .outline(64)
+            .into_iter()
+            .map(|[x, y]| {
+                let (x, y) = ((x - 0.25) * self.chord, y * self.chord);
+                // Rotating clockwise in the y-up frame lifts the leading edge.
+                let (rx, ry) = (x * cos + y * sin, -x * sin + y * cos);
+                [self.position[0] + rx, self.position[1] - ry]
+            })
+            .collect()
+    }
+
+}
+
+/// Force exerted by the fluid on the cells marked in `body`, split into pressure and viscous parts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BodyForce {
+    pub pressure: [f32; 2],
+    pub viscous: [f32; 2],
+}
+
+impl BodyForce {
+    pub fn total(&self) -> [f32; 2] {
+        [self.pressure[0] + self.viscous[0], self.pressure[1] + self.viscous[1]]
+    }
+}
+
+/// Force per unit depth on the obstacles of `solver`, in newtons per meter: the pressure part
+/// from `coverage_pressure_force` and the wall shear integrated over every face between a
+/// `body` cell and a fluid cell.
+///
+/// The solver's pressure is a kinematic pressure scaled by the time step of the last
+/// projection, so it is converted back with `density / delta_time`.
+pub fn body_force(solver: &FluidSolver, velocity_field: &VectorField2D, body: &Grid2<bool>, density: f32, delta_time: f32) -> BodyForce {
+    let mut force = BodyForce { pressure: coverage_pressure_force(solver, velocity_field, density, delta_time), viscous: [0.0; 2] };
+    let [dx, dy] = velocity_field.geometry.spacing;
+
+    for (y, body_row) in body.rows().enumerate().take(solver.height - 1).skip(1) {
+        for (x, &inside) in body_row.iter().enumerate().take(solver.width - 1).skip(1) {
+            if !inside {
+                continue;
+            }
+            // Each face with its tangential velocity component, its length and the distance
+            // between the centers of the cells on either side.
+            let faces = [
+                (x - 1, y, 1, dy, dx),
+                (x + 1, y, 1, dy, dx),
+                (x, y - 1, 0, dx, dy),
+                (x, y + 1, 0, dx, dy),
+            ];
+            for (nx, ny, tangent, length, distance) in faces {
+                if solver.solid[ny][nx] {
+                    continue;
+                }
+
+                // Shear from the tangential velocity of the neighbouring fluid cell, with the
+                // no-slip wall at the solid cell center one cell away.
+                let viscosity = density * solver.viscosity_field.field[ny][nx];
+                let velocity = velocity_field.field[ny][nx];
+                force.viscous[tangent] += viscosity * velocity[tangent] / distance * length;
+            }
+        }
+    }
+
+    force
+}
+
+/// Pressure force on the obstacles of `solver`, including the cells they only partly cover.
+///
+/// The force `-∮ p n dA` over the obstacle surface equals `∫ p ∇χ dV`, where `χ` is the covered
+/// fraction of each cell. It is summed over the faces between cells, so a wall cutting through
+/// cells contributes in proportion to how much it covers instead of in whole-cell steps.
+/// Across a face, `∇χ` is the jump over the cell spacing and the volume is one cell area, so
+/// each jump counts for the length of the face. The result is in newtons per meter of depth.
+pub fn coverage_pressure_force(solver: &FluidSolver, velocity_field: &VectorField2D, density: f32, delta_time: f32) -> [f32; 2] {
+    let mut force = [0.0; 2];
+    let [dx, dy] = velocity_field.geometry.spacing;
+    let face_length = [dy, dx];
+    let covered = |x: usize, y: usize| if solver.solid[y][x] { 1.0 } else { 1.0 - solver.fluid_fraction.field[y][x] };
+
+    for y in 0..solver.height {
+        for x in 0..solver.width {
+            for (axis, (nx, ny)) in [(x + 1, y), (x, y + 1)].into_iter().enumerate() {
+                if nx >= solver.width || ny >= solver.height {
+                    continue;
+                }
+                let jump = covered(nx, ny) - covered(x, y);
+                if jump == 0.0 {
+                    continue;
+                }
+                let pressure = match (solver.solid[y][x], solver.solid[ny][nx]) {
+                    (true, true) => continue,
+                    (true, false) => solver.pressure.field[ny][nx],
+                    (false, true) => solver.pressure.field[y][x],
+                    (false, false) => 0.5 * (solver.pressure.field[y][x] + solver.pressure.field[ny][nx]),
+                };
+                force[axis] += density * pressure / delta_time * jump * face_length[axis];
+            }
+        }
+    }
+
+    force
+}
+
+/// Drag and lift coefficients for a flow along +x, with lift positive upwards (towards -y).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ForceCoefficients {
+    pub drag: f32,
+    pub lift: f32,
+}
+
+impl ForceCoefficients {
+    /// Coefficients of a force per unit depth, in newtons per meter, on a body whose
+    /// `reference_length` (chord or frontal height) is given in meters.
+    pub fn from_force(force: [f32; 2], density: f32, speed: f32, reference_length: f32) -> Self {
+        let dynamic_pressure = 0.5 * density * speed * speed * reference_length;
+        Self {
+            drag: force[0] / dynamic_pressure,
+            lift: -force[1] / dynamic_pressure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::support::sdf::Sdf;
+    use crate::support::solver::EdgeCondition;
+    use crate::support::viscosity::ViscosityModel;
+
+    #[test]
+    fn symmetric_section_at_zero_incidence_has_drag_and_no_lift() {
+        let (width, height, speed, delta_time) = (64, 32, 2.0, 0.1);
+        let mut solver = FluidSolver::new(width, height);
+        solver.viscosity = ViscosityModel::Newtonian(0.02);
+        solver.iterations = 40;
+        solver.edges = [EdgeCondition::Inflow([speed, 0.0]), EdgeCondition::Outflow, EdgeCondition::Wall, EdgeCondition::Wall];
+        let airfoil = Airfoil { section: Naca4::parse("0012").unwrap(), position: [20.0, 16.0], chord: 16.0, angle_of_attack: 0.0 };
+        let mut velocity_field = VectorField2D::new(width, height, [speed, 0.0]);
+        solver.add_obstacle(&Sdf::polygon(airfoil.polygon()).in_cells(velocity_field.geometry));
+        let body = solver.solid.clone();
+
+        for _ in 0..60 {
+            solver.step(&mut velocity_field, delta_time);
+        }
+        let force = body_force(&solver, &velocity_field, &body, 1.0, delta_time);
+        let coefficients = ForceCoefficients::from_force(force.total(), 1.0, speed, airfoil.chord);
+        assert!(coefficients.drag > 0.0, "{coefficients:?}");
+        // The section is mirrored about the middle row, so only rounding leaves any lift.
+        assert!(coefficients.lift.abs() < 0.01, "{coefficients:?}");
+    }
+}
@@ -35,7 +35,7 @@ impl CameraState {
     }
 
     pub fn get_perspective(&self) -> [[f32; 4]; 4] {
-        let fov: f32 = 3.141592 / 2.0;
+        let fov: f32 = std::f32::consts::PI / 2.0;
         let zfar = 1024.0;
         let znear = 0.1;
 
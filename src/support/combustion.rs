@@ -0,0 +1,130 @@
+use super::field::{ColorField2D, VectorField2D};
+use super::real::Real;
+
+/// Fuel, temperature and smoke carried by the flow, with a one-step burn reaction.
+///
+/// Wherever the temperature is above `ignition_temperature`, fuel is consumed at
+/// `burn_rate` per unit time, releasing `heat_release` degrees and `smoke_yield`
+/// smoke per unit of fuel. Hot gas rises and smoke sinks through the buoyancy force
+/// applied to the velocity field. Rows grow downwards, so "up" is negative y.
+#[derive(Debug, Clone)]
+pub struct Combustion<T: Real = f32> {
+    pub fuel: ColorField2D<T>,
+    pub temperature: ColorField2D<T>,
+    pub smoke: ColorField2D<T>,
+    pub ambient_temperature: T,
+    pub ignition_temperature: T,
+    pub burn_rate: T,
+    pub heat_release: T,
+    pub smoke_yield: T,
+    pub cooling_rate: T,
+    pub smoke_dissipation: T,
+    pub buoyancy: T,
+    pub smoke_weight: T,
+}
+
+impl<T: Real> Combustion<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        let ambient_temperature = T::ZERO;
+        Self {
+            fuel: ColorField2D::new(width, height, T::ZERO),
+            temperature: ColorField2D::new(width, height, ambient_temperature),
+            smoke: ColorField2D::new(width, height, T::ZERO),
+            ambient_temperature,
+            ignition_temperature: T::from_f32(0.25),
+            burn_rate: T::from_f32(2.0),
+            heat_release: T::from_f32(1.5),
+            smoke_yield: T::from_f32(0.3),
+            cooling_rate: T::from_f32(0.8),
+            smoke_dissipation: T::from_f32(0.2),
+            buoyancy: T::from_f32(6.0),
+            smoke_weight: T::ONE,
+        }
+    }
+
+    /// Adds fuel in a disc; `position` and `radius` are in meters.
+    pub fn add_fuel(&mut self, position: [T; 2], radius: T, amount: T) {
+        self.fuel.splat(position, radius, amount);
+    }
+
+    /// Raises the temperature around a point, e.g. to ignite fuel.
+    pub fn add_heat(&mut self, position: [T; 2], radius: T, temperature: T) {
+        self.temperature.splat(position, radius, temperature);
+    }
+
+    /// Carries fuel, temperature and smoke over to a `width` x `height` grid covering the
+    /// same area.
+    pub fn resample(&mut self, width: usize, height: usize) {
+        self.fuel = self.fuel.resample(width, height);
+        self.temperature = self.temperature.resample(width, height);
+        self.smoke = self.smoke.resample(width, height);
+    }
+
+    pub fn update(&mut self, velocity_field: &mut VectorField2D<T>, delta_time: T) {
+        self.fuel = self.fuel.update(velocity_field, delta_time);
+        self.temperature = self.temperature.update(velocity_field, delta_time);
+        self.smoke = self.smoke.update(velocity_field, delta_time);
+
+        let cooling = (-self.cooling_rate * delta_time).exp();
+        let dissipation = (-self.smoke_dissipation * delta_time).exp();
+
+        for y in 0..self.fuel.height() {
+            for x in 0..self.fuel.width() {
+                let fuel = &mut self.fuel.field[y][x];
+                let temperature = &mut self.temperature.field[y][x];
+                let smoke = &mut self.smoke.field[y][x];
+
+                if *temperature >= self.ignition_temperature && *fuel > T::ZERO {
+                    let burned = fuel.min(self.burn_rate * delta_time);
+                    *fuel -= burned;
+                    *temperature += self.heat_release * burned;
+                    *smoke += self.smoke_yield * burned;
+                }
+
+                *temperature = self.ambient_temperature + (*temperature - self.ambient_temperature) * cooling;
+                *smoke *= dissipation;
+
+                let lift = self.buoyancy * (*temperature - self.ambient_temperature) - self.smoke_weight * *smoke;
+                velocity_field.field[y][x][1] -= lift * delta_time;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(field: &ColorField2D) -> f32 {
+        field.field.cells().sum()
+    }
+
+    #[test]
+    fn cold_fuel_does_not_burn() {
+        let mut combustion = Combustion::new(16, 16);
+        let mut velocity_field = VectorField2D::new(16, 16, [0.0, 0.0]);
+        combustion.add_fuel([8.0, 8.0], 3.0, 1.0);
+        let fuel = total(&combustion.fuel);
+
+        combustion.update(&mut velocity_field, 0.1);
+
+        assert!((total(&combustion.fuel) - fuel).abs() < 1e-4);
+        assert_eq!(total(&combustion.smoke), 0.0);
+        assert!(velocity_field.field.cells().all(|velocity| *velocity == [0.0, 0.0]));
+    }
+
+    #[test]
+    fn ignited_fuel_burns_into_rising_smoke() {
+        let mut combustion = Combustion::new(16, 16);
+        let mut velocity_field = VectorField2D::new(16, 16, [0.0, 0.0]);
+        combustion.add_fuel([8.0, 8.0], 3.0, 1.0);
+        combustion.add_heat([8.0, 8.0], 3.0, 1.0);
+        let fuel = total(&combustion.fuel);
+
+        combustion.update(&mut velocity_field, 0.1);
+
+        assert!(total(&combustion.fuel) < fuel);
+        assert!(total(&combustion.smoke) > 0.0);
+        assert!(velocity_field.field[8][8][1] < 0.0, "hot gas should rise");
+    }
+}
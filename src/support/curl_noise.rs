@@ -0,0 +1,197 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::field::{ColorField2D, VectorField2D};
+
+/// Divergence-free turbulence: the velocity is the curl `(dpsi/dy, -dpsi/dx)` of a fractal
+/// Perlin noise potential `psi`, so it swirls without compressing or expanding the fluid and
+/// barely disturbs the pressure solve. The potential is sampled in 3D with time as the third
+/// coordinate, so the eddies evolve smoothly instead of staying frozen in place.
+#[derive(Debug, Clone)]
+pub struct CurlNoise {
+    /// Size of the largest eddies, in meters.
+    pub scale: f32,
+    pub octaves: usize,
+    /// Frequency multiplier between octaves.
+    pub lacunarity: f32,
+    /// Potential amplitude multiplier between octaves. With `lacunarity * persistence = 1`,
+    /// every octave adds eddies of the same speed.
+    pub persistence: f32,
+    /// Peak velocity added per unit time, in meters per second squared.
+    pub strength: f32,
+    /// How fast the pattern changes, in noise periods per unit time.
+    pub evolution_speed: f32,
+    pub time: f32,
+    permutation: [u8; 512],
+}
+
+impl CurlNoise {
+    pub fn new(seed: u64) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = table[i % 256];
+        }
+
+        Self {
+            scale: 24.0,
+            octaves: 3,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            strength: 1.0,
+            evolution_speed: 0.2,
+            time: 0.0,
+            permutation,
+        }
+    }
+
+    /// Fractal noise potential at a point in meters, normalized so its curl peaks at about one
+    /// meter per second whatever the scale and octaves.
+    pub fn potential(&self, x: f32, y: f32) -> f32 {
+        let mut frequency = 1.0 / self.scale;
+        let mut amplitude = self.scale;
+        let mut total_weight = 0.0;
+        let mut value = 0.0;
+        for octave in 0..self.octaves {
+            // Offset each octave so they do not share a lattice origin.
+            let offset = octave as f32 * 17.31;
+            value += amplitude * self.perlin(x * frequency + offset, y * frequency + offset, self.time * self.evolution_speed + offset);
+            total_weight += amplitude * frequency;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+        value / total_weight.max(f32::EPSILON)
+    }
+
+    /// Curl of the potential at `position`, in meters, by central differences `step` meters apart.
+    pub fn velocity(&self, position: [f32; 2], step: f32) -> [f32; 2] {
+        curl(|x, y| self.potential(x, y), position, step)
+    }
+
+    /// Curl of the potential multiplied by `weight` (clamped to `[0, 1]`). Weighting the
+    /// potential rather than the velocity keeps the result divergence free: it adds a
+    /// `psi * curl(weight)` term that swirls along the edges of the weighted region.
+    pub fn weighted_velocity(&self, position: [f32; 2], step: f32, weight: &ColorField2D) -> [f32; 2] {
+        let weighted_potential = |x: f32, y: f32| {
+            let weight = weight.sample([x, y]).clamp(0.0, 1.0);
+            if weight == 0.0 {
+                0.0
+            } else {
+                weight * self.potential(x, y)
+            }
+        };
+        curl(weighted_potential, position, step)
+    }
+
+    /// Adds the turbulence to `velocity_field` as a force over `delta_time` and lets the
+    /// pattern evolve. With a `weight`, the potential is scaled by it before taking the curl
+    /// (see `weighted_velocity`), e.g. to stir only where there is smoke. Derivatives are
+    /// taken half a cell apart.
+    pub fn apply(&mut self, velocity_field: &mut VectorField2D, weight: Option<&ColorField2D>, delta_time: f32) {
+        let scale = self.strength * delta_time;
+        let geometry = velocity_field.geometry;
+        let step = 0.5 * geometry.spacing[0].min(geometry.spacing[1]);
+        for (y, row) in velocity_field.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let position = geometry.position(x as f32, y as f32);
+                let [u, v] = match weight {
+                    Some(weight) => self.weighted_velocity(position, step, weight),
+                    None => self.velocity(position, step),
+                };
+                value[0] += u * scale;
+                value[1] += v * scale;
+            }
+        }
+        self.time += delta_time;
+    }
+
+    /// Improved Perlin noise (Perlin 2002) in three dimensions, roughly in `[-1, 1]`.
+    fn perlin(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (xi, yi, zi) = (x.floor(), y.floor(), z.floor());
+        let (x, y, z) = (x - xi, y - yi, z - zi);
+        let (xi, yi, zi) = ((xi as i32 & 255) as usize, (yi as i32 & 255) as usize, (zi as i32 & 255) as usize);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.permutation;
+        let a = p[xi] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let b = p[xi + 1] as usize + yi;
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1.0, y, z)),
+                lerp(u, gradient(p[ab], x, y - 1.0, z), gradient(p[bb], x - 1.0, y - 1.0, z)),
+            ),
+            lerp(
+                v,
+                lerp(u, gradient(p[aa + 1], x, y, z - 1.0), gradient(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, gradient(p[ab + 1], x, y - 1.0, z - 1.0), gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0)),
+            ),
+        )
+    }
+}
+
+/// Curl `(dpsi/dy, -dpsi/dx)` of `potential`, by central differences `h` apart.
+fn curl(potential: impl Fn(f32, f32) -> f32, [x, y]: [f32; 2], h: f32) -> [f32; 2] {
+    let dpsi_dx = (potential(x + h, y) - potential(x - h, y)) / (2.0 * h);
+    let dpsi_dy = (potential(x, y + h) - potential(x, y - h)) / (2.0 * h);
+    [dpsi_dy, -dpsi_dx]
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// Dot product with one of twelve edge directions of a cube, picked by `hash`.
+fn gradient(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::support::grid::GridGeometry;
+
+    #[test]
+    fn stirs_without_compressing() {
+        let mut noise = CurlNoise::new(3);
+        // Quarter-meter cells, so even the smallest eddies span many cells.
+        let mut velocity_field = VectorField2D::new(64, 64, [0.0, 0.0]).with_geometry(GridGeometry::uniform(0.25));
+        noise.apply(&mut velocity_field, None, 1.0);
+
+        // Central differences of the velocity, in units of the cell size.
+        let field = &velocity_field.field;
+        let (mut divergence, mut shear): (f32, f32) = (0.0, 0.0);
+        for y in 1..63 {
+            for x in 1..63 {
+                let dudx = 0.5 * (field[y][x + 1][0] - field[y][x - 1][0]);
+                let dvdy = 0.5 * (field[y + 1][x][1] - field[y - 1][x][1]);
+                let dudy = 0.5 * (field[y + 1][x][0] - field[y - 1][x][0]);
+                divergence = divergence.max((dudx + dvdy).abs());
+                shear = shear.max(dudx.abs()).max(dudy.abs());
+            }
+        }
+        assert!(divergence < 0.01 * shear, "divergence {divergence} against velocity gradients of {shear}");
+
+        let peak = velocity_field.field.cells().map(|[u, v]| (u * u + v * v).sqrt()).fold(0.0, f32::max);
+        assert!((0.3..3.0).contains(&peak), "peak speed {peak}");
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::field::{ColorField2D, VectorField2D};
+use super::real::Real;
+
+/// Health indicators of a flow at one instant, in the SI units set by the velocity field's
+/// geometry. Integrals are per unit depth and density, so the energy is in `m^4/s^2`.
+/// They are accumulated in the precision of the fields and reported in `f32`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Diagnostics {
+    /// Largest absolute velocity divergence over the inner cells, in `1/s`; zero for a perfectly projected flow.
+    pub max_divergence: f32,
+    /// Root mean square divergence over the inner cells.
+    pub l2_divergence: f32,
+    /// `0.5 |u|^2` integrated over the domain.
+    pub kinetic_energy: f32,
+    /// `0.5 w^2` integrated over the domain, where `w` is the vorticity.
+    pub enstrophy: f32,
+    /// Dye concentration integrated over the domain, if the flow carries one.
+    pub dye_mass: Option<f32>,
+    /// Largest distance, in cells, travelled by the flow in one time step of `delta_time` seconds.
+    pub max_cfl: f32,
+}
+
+impl Diagnostics {
+    pub fn measure<T: Real>(velocity_field: &VectorField2D<T>, dye: Option<&ColorField2D<T>>, delta_time: f32) -> Self {
+        let (width, height) = (velocity_field.width(), velocity_field.height());
+        let field = &velocity_field.field;
+        let geometry = velocity_field.geometry;
+        let [dx, dy] = geometry.spacing;
+        let area = geometry.cell_area();
+        let half = T::from_f32(0.5);
+
+        let mut max_divergence = T::ZERO;
+        let mut sum_squared_divergence = T::ZERO;
+        for y in 1..height.saturating_sub(1) {
+            for x in 1..width.saturating_sub(1) {
+                let divergence = half * ((field[y][x + 1][0] - field[y][x - 1][0]) / dx + (field[y + 1][x][1] - field[y - 1][x][1]) / dy);
+                max_divergence = max_divergence.max(divergence.abs());
+                sum_squared_divergence += divergence * divergence;
+            }
+        }
+        let inner_cells = width.saturating_sub(2) * height.saturating_sub(2);
+        let l2_divergence = if inner_cells > 0 { (sum_squared_divergence / T::from_usize(inner_cells)).sqrt() } else { T::ZERO };
+
+        // Energy, enstrophy and largest speed in cells per second of each row.
+        let rows = field.par_rows(|y, row| {
+            let (mut kinetic_energy, mut enstrophy, mut max_cell_speed) = (T::ZERO, T::ZERO, T::ZERO);
+            for (x, &[u, v]) in row.iter().enumerate() {
+                kinetic_energy += half * (u * u + v * v) * area;
+                enstrophy += half * velocity_field.curl(x, y).powi(2) * area;
+                max_cell_speed = max_cell_speed.max(u.abs() / dx).max(v.abs() / dy);
+            }
+            (kinetic_energy, enstrophy, max_cell_speed)
+        });
+        let kinetic_energy: T = rows.iter().map(|row| row.0).sum();
+        let enstrophy: T = rows.iter().map(|row| row.1).sum();
+        let max_cell_speed = rows.iter().fold(T::ZERO, |max, row| max.max(row.2));
+
+        Self {
+            max_divergence: max_divergence.to_f32(),
+            l2_divergence: l2_divergence.to_f32(),
+            kinetic_energy: kinetic_energy.to_f32(),
+            enstrophy: enstrophy.to_f32(),
+            dye_mass: dye.map(|dye| (dye.field.cells().copied().sum::<T>() * dye.geometry.cell_area()).to_f32()),
+            max_cfl: max_cell_speed.to_f32() * delta_time,
+        }
+    }
+
+    pub const CSV_HEADER: &'static str = "step,time,max_divergence,l2_divergence,kinetic_energy,enstrophy,dye_mass,max_cfl";
+
+    /// One CSV line matching `CSV_HEADER`, with an empty dye mass when there is no dye.
+    pub fn csv_row(&self, step: usize, time: f32) -> String {
+        let dye_mass = self.dye_mass.map_or(String::new(), |mass| mass.to_string());
+        format!(
+            "{step},{time},{},{},{},{},{dye_mass},{}",
+            self.max_divergence, self.l2_divergence, self.kinetic_energy, self.enstrophy, self.max_cfl
+        )
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "div max {:.2e} l2 {:.2e}  energy {:.4e}  enstrophy {:.4e}  CFL {:.2}",
+            self.max_divergence, self.l2_divergence, self.kinetic_energy, self.enstrophy, self.max_cfl
+        )?;
+        if let Some(mass) = self.dye_mass {
+            write!(f, "  dye {mass:.4e}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes one CSV row of diagnostics per call to a file.
+pub struct DiagnosticsLog {
+    writer: BufWriter<File>,
+}
+
+impl DiagnosticsLog {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", Diagnostics::CSV_HEADER)?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, step: usize, time: f32, diagnostics: &Diagnostics) -> io::Result<()> {
+        writeln!(self.writer, "{}", diagnostics.csv_row(step, time))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
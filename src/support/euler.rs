@@ -0,0 +1,361 @@
+use super::field::{ColorField2D, VectorField2D};
+use super::grid::Grid2;
+use super::real::Real;
+
+/// What happens to the gas at one edge of the domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasBoundary {
+    /// Solid wall: the gas bounces back.
+    Reflective,
+    /// Waves leave the domain without reflecting (zero gradient).
+    Transmissive,
+}
+
+/// Conserved state `(rho, rho u, rho v, E)` of one cell.
+type Conserved<T> = [T; 4];
+/// Primitive state `(rho, u, v, p)` of one cell.
+type Primitive<T> = [T; 4];
+
+/// Density and pressure never drop below this, so strong rarefactions cannot produce
+/// negative values and NaNs.
+const FLOOR: f32 = 1e-6;
+
+/// Compressible Euler equations for an ideal gas, solved with a finite-volume HLLC Riemann
+/// solver (Toro), MUSCL reconstruction of the primitive variables with a minmod limiter and
+/// second-order SSP Runge-Kutta time stepping. Shocks stay sharp over two or three cells
+/// without oscillations.
+#[derive(Debug, Clone)]
+pub struct CompressibleEuler<T: Real = f32> {
+    pub width: usize,
+    pub height: usize,
+    pub density: ColorField2D<T>,
+    pub momentum: VectorField2D<T>,
+    /// Total energy per unit volume, `p / (gamma - 1) + rho |u|^2 / 2`.
+    pub energy: ColorField2D<T>,
+    /// Ratio of specific heats.
+    pub gamma: T,
+    /// Width of a cell in domain units.
+    pub cell_size: T,
+    pub courant: T,
+    /// Left, right, top and bottom edges.
+    pub boundaries: [GasBoundary; 4],
+}
+
+impl<T: Real> CompressibleEuler<T> {
+    pub fn new(width: usize, height: usize, cell_size: T) -> Self {
+        let gamma = T::from_f32(1.4);
+        Self {
+            width,
+            height,
+            density: ColorField2D::new(width, height, T::ONE),
+            momentum: VectorField2D::new(width, height, [T::ZERO; 2]),
+            energy: ColorField2D::new(width, height, T::ONE / (gamma - T::ONE)),
+            gamma,
+            cell_size,
+            courant: T::from_f32(0.4),
+            boundaries: [GasBoundary::Transmissive; 4],
+        }
+    }
+
+    /// Sets a cell from its density, velocity and pressure.
+    pub fn set_primitive(&mut self, x: usize, y: usize, density: T, velocity: [T; 2], pressure: T) {
+        let [rho, rho_u, rho_v, energy] = self.conserved([density, velocity[0], velocity[1], pressure]);
+        self.density.field[y][x] = rho;
+        self.momentum.field[y][x] = [rho_u, rho_v];
+        self.energy.field[y][x] = energy;
+    }
+
+    /// Fills every cell from `state`, called with the cell center in domain units and
+    /// returning density, velocity and pressure.
+    pub fn initialize(&mut self, state: impl Fn(T, T) -> (T, [T; 2], T)) {
+        let half = T::from_f32(0.5);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (density, velocity, pressure) = state((T::from_usize(x) + half) * self.cell_size, (T::from_usize(y) + half) * self.cell_size);
+                self.set_primitive(x, y, density, velocity, pressure);
+            }
+        }
+    }
+
+    pub fn velocity(&self, x: usize, y: usize) -> [T; 2] {
+        let density = self.density.field[y][x];
+        let [rho_u, rho_v] = self.momentum.field[y][x];
+        [rho_u / density, rho_v / density]
+    }
+
+    pub fn pressure(&self, x: usize, y: usize) -> T {
+        self.primitive(self.state(x, y))[3]
+    }
+
+    /// Local Mach number `|u| / c`.
+    pub fn mach(&self, x: usize, y: usize) -> T {
+        let [density, u, v, pressure] = self.primitive(self.state(x, y));
+        (u * u + v * v).sqrt() / self.sound_speed(density, pressure)
+    }
+
+    /// Velocity of every cell, in cells per unit time.
+    pub fn velocity_field(&self) -> VectorField2D<T> {
+        let mut velocity_field = VectorField2D::new(self.width, self.height, [T::ZERO; 2]);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let [u, v] = self.velocity(x, y);
+                velocity_field.field[y][x] = [u / self.cell_size, v / self.cell_size];
+            }
+        }
+        velocity_field
+    }
+
+    /// Largest stable time step for the current state.
+    pub fn max_time_step(&self) -> T {
+        let mut max_speed = T::from_f32(1e-6);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let [density, u, v, pressure] = self.primitive(self.state(x, y));
+                let c = self.sound_speed(density, pressure);
+                max_speed = max_speed.max(u.abs() + c).max(v.abs() + c);
+            }
+        }
+        self.courant * self.cell_size / max_speed
+    }
+
+    /// Advances the state by `delta_time`, taking as many stable substeps as needed.
+    pub fn advance(&mut self, delta_time: T) {
+        let mut remaining = delta_time;
+        while remaining > T::ZERO {
+            let step = self.max_time_step().min(remaining);
+            self.step(step);
+            remaining -= step;
+        }
+    }
+
+    /// One step of the second-order strong-stability-preserving Runge-Kutta scheme (Heun).
+    pub fn step(&mut self, delta_time: T) {
+        let initial = self.states();
+
+        let mut stage = initial.clone();
+        self.add_residual(&initial, &mut stage, delta_time);
+
+        let mut result = stage.clone();
+        self.add_residual(&stage, &mut result, delta_time);
+        for (state, initial) in result.cells_mut().zip(initial.cells()) {
+            for (value, initial) in state.iter_mut().zip(initial) {
+                *value = T::from_f32(0.5) * (*value + *initial);
+            }
+        }
+
+        for (y, row) in result.rows().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                let [density, u, v, pressure] = self.primitive(state);
+                self.set_primitive(x, y, density, [u, v], pressure);
+            }
+        }
+    }
+
+    fn state(&self, x: usize, y: usize) -> Conserved<T> {
+        let [rho_u, rho_v] = self.momentum.field[y][x];
+        [self.density.field[y][x], rho_u, rho_v, self.energy.field[y][x]]
+    }
+
+    fn states(&self) -> Grid2<Conserved<T>> {
+        Grid2::from_fn(self.width, self.height, |x, y| self.state(x, y))
+    }
+
+    fn primitive(&self, state: Conserved<T>) -> Primitive<T> {
+        let [rho, rho_u, rho_v, energy] = state;
+        let floor = T::from_f32(FLOOR);
+        let density = rho.max(floor);
+        let (u, v) = (rho_u / density, rho_v / density);
+        let pressure = ((self.gamma - T::ONE) * (energy - T::from_f32(0.5) * density * (u * u + v * v))).max(floor);
+        [density, u, v, pressure]
+    }
+
+    fn conserved(&self, primitive: Primitive<T>) -> Conserved<T> {
+        let [density, u, v, pressure] = primitive;
+        [density, density * u, density * v, pressure / (self.gamma - T::ONE) + T::from_f32(0.5) * density * (u * u + v * v)]
+    }
+
+    fn sound_speed(&self, density: T, pressure: T) -> T {
+        (self.gamma * pressure / density).sqrt()
+    }
+
+    /// Primitive state at `index` along a line of cells normal to `axis`, with ghost cells
+    /// outside the domain filled according to the boundaries.
+    fn line_primitive(&self, line: &[Primitive<T>], index: isize, axis: usize) -> Primitive<T> {
+        let last = line.len() as isize - 1;
+        let (boundary, mirrored) = if index < 0 {
+            (self.boundaries[2 * axis], -index - 1)
+        } else if index > last {
+            (self.boundaries[2 * axis + 1], 2 * last - index + 1)
+        } else {
+            return line[index as usize];
+        };
+
+        match boundary {
+            GasBoundary::Reflective => {
+                let mut state = line[mirrored.clamp(0, last) as usize];
+                state[1 + axis] = -state[1 + axis];
+                state
+            }
+            GasBoundary::Transmissive => line[index.clamp(0, last) as usize],
+        }
+    }
+
+    /// Adds `delta_time` times the flux divergence of `states` to `result`.
+    fn add_residual(&self, states: &Grid2<Conserved<T>>, result: &mut Grid2<Conserved<T>>, delta_time: T) {
+        let scale = delta_time / self.cell_size;
+        let (half, floor) = (T::from_f32(0.5), T::from_f32(FLOOR));
+
+        for axis in 0..2 {
+            let (lines, length) = if axis == 0 { (self.height, self.width) } else { (self.width, self.height) };
+            for line_index in 0..lines {
+                let cell = |i: usize| if axis == 0 { (i, line_index) } else { (line_index, i) };
+                let line: Vec<Primitive<T>> = (0..length)
+                    .map(|i| {
+                        let (x, y) = cell(i);
+                        self.primitive(states[y][x])
+                    })
+                    .collect();
+
+                let reconstruct = |i: isize, side: T| {
+                    let (previous, center, next) = (
+                        self.line_primitive(&line, i - 1, axis),
+                        self.line_primitive(&line, i, axis),
+                        self.line_primitive(&line, i + 1, axis),
+                    );
+                    let mut face = center;
+                    for k in 0..4 {
+                        face[k] += side * half * minmod(center[k] - previous[k], next[k] - center[k]);
+                    }
+                    face[0] = face[0].max(floor);
+                    face[3] = face[3].max(floor);
+                    face
+                };
+
+                // Face `i` lies between cells `i - 1` and `i`.
+                for face in 0..=length as isize {
+                    let flux = self.hllc_flux(reconstruct(face - 1, T::ONE), reconstruct(face, -T::ONE), axis);
+                    if face > 0 {
+                        let (x, y) = cell(face as usize - 1);
+                        for k in 0..4 {
+                            result[y][x][k] -= scale * flux[k];
+                        }
+                    }
+                    if face < length as isize {
+                        let (x, y) = cell(face as usize);
+                        for k in 0..4 {
+                            result[y][x][k] += scale * flux[k];
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// HLLC flux across a face normal to `axis`, with wave speed estimates from Davis.
+    fn hllc_flux(&self, left: Primitive<T>, right: Primitive<T>, axis: usize) -> Conserved<T> {
+        let normal = 1 + axis;
+        let physical_flux = |state: Primitive<T>, conserved: Conserved<T>| {
+            let velocity = state[normal];
+            let mut flux = conserved.map(|value| value * velocity);
+            flux[normal] += state[3];
+            flux[3] += state[3] * velocity;
+            flux
+        };
+
+        let (conserved_left, conserved_right) = (self.conserved(left), self.conserved(right));
+        let (c_left, c_right) = (self.sound_speed(left[0], left[3]), self.sound_speed(right[0], right[3]));
+        let (u_left, u_right) = (left[normal], right[normal]);
+
+        let speed_left = (u_left - c_left).min(u_right - c_right);
+        let speed_right = (u_left + c_left).max(u_right + c_right);
+        if speed_left >= T::ZERO {
+            return physical_flux(left, conserved_left);
+        }
+        if speed_right <= T::ZERO {
+            return physical_flux(right, conserved_right);
+        }
+
+        let mass_left = left[0] * (speed_left - u_left);
+        let mass_right = right[0] * (speed_right - u_right);
+        let speed_star = (right[3] - left[3] + u_left * mass_left - u_right * mass_right) / (mass_left - mass_right);
+
+        // Star state on one side of the contact, and the flux jumping to it across the outer wave.
+        let star_flux = |state: Primitive<T>, conserved: Conserved<T>, speed: T, mass: T| {
+            let factor = mass / (speed - speed_star);
+            let mut star = [factor, factor * state[1], factor * state[2], T::ZERO];
+            star[normal] = factor * speed_star;
+            star[3] = factor * (conserved[3] / state[0] + (speed_star - state[normal]) * (speed_star + state[3] / mass));
+
+            let mut flux = physical_flux(state, conserved);
+            for k in 0..4 {
+                flux[k] += speed * (star[k] - conserved[k]);
+            }
+            flux
+        };
+
+        if speed_star >= T::ZERO {
+            star_flux(left, conserved_left, speed_left, mass_left)
+        } else {
+            star_flux(right, conserved_right, speed_right, mass_right)
+        }
+    }
+}
+
+/// Slope limiter that picks the smaller one-sided difference, or zero at extrema.
+fn minmod<T: Real>(a: T, b: T) -> T {
+    if a * b <= T::ZERO {
+        T::ZERO
+    } else if a.abs() < b.abs() {
+        a
+    } else {
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sod's shock tube at `t = 0.2` on 400 cells, against the exact Riemann solution (Toro,
+    /// section 4.3): star pressure 0.30313, star densities 0.42632 and 0.26557, shock at 0.8504.
+    #[test]
+    fn sod_shock_tube_matches_the_exact_solution() {
+        let cells = 400;
+        let mut gas = CompressibleEuler::<f64>::new(cells, 2, 1.0 / cells as f64);
+        gas.initialize(|x, _| if x < 0.5 { (1.0, [0.0, 0.0], 1.0) } else { (0.125, [0.0, 0.0], 0.1) });
+        gas.advance(0.2);
+
+        let density = |x: f64| gas.density.field[0][(x * cells as f64) as usize];
+        let pressure = |x: f64| gas.pressure((x * cells as f64) as usize, 0);
+        // Left star region, between the rarefaction tail (0.486) and the contact (0.685).
+        assert!((density(0.6) - 0.42632).abs() < 0.01, "left plateau {}", density(0.6));
+        // Right star region, between the contact and the shock.
+        assert!((density(0.78) - 0.26557).abs() < 0.01, "right plateau {}", density(0.78));
+        assert!((pressure(0.6) - 0.30313).abs() < 0.005, "star pressure {}", pressure(0.6));
+
+        let shock = (0..cells).rev().find(|&x| gas.density.field[0][x] > 0.5 * (0.125 + 0.26557)).unwrap();
+        let shock = (shock as f64 + 0.5) / cells as f64;
+        assert!((shock - 0.8504).abs() < 2.0 / cells as f64, "shock at {shock}");
+    }
+
+    #[test]
+    fn closed_box_conserves_mass_and_energy() {
+        let mut gas = CompressibleEuler::<f64>::new(48, 48, 1.0 / 48.0);
+        gas.boundaries = [GasBoundary::Reflective; 4];
+        gas.initialize(|x, y| {
+            let blast = (x - 0.3).powi(2) + (y - 0.4).powi(2) < 0.01;
+            (if blast { 2.0 } else { 1.0 }, [0.2, -0.1], if blast { 5.0 } else { 1.0 })
+        });
+        let totals = |gas: &CompressibleEuler<f64>| {
+            let mass: f64 = gas.density.field.cells().sum();
+            let energy: f64 = gas.energy.field.cells().sum();
+            (mass, energy)
+        };
+        let (mass, energy) = totals(&gas);
+        gas.advance(0.5);
+        let (final_mass, final_energy) = totals(&gas);
+
+        assert!((final_mass / mass - 1.0).abs() < 1e-12, "mass {mass} -> {final_mass}");
+        assert!((final_energy / energy - 1.0).abs() < 1e-12, "energy {energy} -> {final_energy}");
+    }
+}
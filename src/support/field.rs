@@ -1,114 +1,303 @@
-#[derive(Debug, Clone)]
-pub struct VectorField2D {
-    pub width: usize,
-    pub height: usize,
-    pub field: Vec<Vec<[f32; 2]>>,
+use super::grid::{Grid2, GridGeometry, OutOfBounds};
+use super::real::Real;
+
+/// O que um `Field2D` guarda em cada célula: um escalar ou um vetor de escalares na precisão
+/// `Scalar`, interpolados e acumulados componente a componente.
+pub trait CellValue: Copy + Send + Sync + 'static {
+    type Scalar: Real;
+    /// O mesmo tipo de valor em outra precisão.
+    type Cast<U: Real>: CellValue<Scalar = U>;
+
+    const ZERO: Self;
+
+    /// `a + (b - a) * t`, que devolve exatamente `a` quando `a == b`.
+    fn lerp(a: Self, b: Self, t: Self::Scalar) -> Self;
+    fn add_scaled(self, other: Self, scale: Self::Scalar) -> Self;
+    fn cast<U: Real>(self) -> Self::Cast<U>;
+
+    fn scale(self, factor: Self::Scalar) -> Self {
+        Self::ZERO.add_scaled(self, factor)
+    }
+}
+
+impl<T: Real> CellValue for T {
+    type Scalar = T;
+    type Cast<U: Real> = U;
+
+    const ZERO: Self = T::ZERO;
+
+    fn lerp(a: Self, b: Self, t: T) -> Self {
+        a + (b - a) * t
+    }
+
+    fn add_scaled(self, other: Self, scale: T) -> Self {
+        self + other * scale
+    }
+
+    fn cast<U: Real>(self) -> U {
+        U::from_f64(self.to_f64())
+    }
+}
+
+impl<T: Real, const N: usize> CellValue for [T; N] {
+    type Scalar = T;
+    type Cast<U: Real> = [U; N];
+
+    const ZERO: Self = [T::ZERO; N];
+
+    fn lerp(mut a: Self, b: Self, t: T) -> Self {
+        for (a, b) in a.iter_mut().zip(b) {
+            *a += (b - *a) * t;
+        }
+        a
+    }
+
+    fn add_scaled(mut self, other: Self, scale: T) -> Self {
+        for (value, other) in self.iter_mut().zip(other) {
+            *value += other * scale;
+        }
+        self
+    }
+
+    fn cast<U: Real>(self) -> [U; N] {
+        self.map(|c| U::from_f64(c.to_f64()))
+    }
 }
 
+/// Grade de valores posicionada no espaço físico por `geometry`, advectada de forma
+/// semi-Lagrangiana por um campo de velocidades.
 #[derive(Debug, Clone)]
-pub struct ColorField2D {
-    pub width: usize,
-    pub height: usize,
-    pub field: Vec<Vec<f32>>,
+pub struct Field2D<V: CellValue> {
+    pub geometry: GridGeometry<V::Scalar>,
+    pub field: Grid2<V>,
 }
 
-impl ColorField2D {
-    pub fn new(width: usize, height: usize, initial_value: f32) -> Self {
-        let field = vec![vec![initial_value; width]; height];
-        Self {
-            width,
-            height,
-            field,
+/// Campo de velocidades em metros por segundo, no espaçamento dado por `geometry`.
+pub type VectorField2D<T = f32> = Field2D<[T; 2]>;
+
+/// Campo escalar, como corante, temperatura ou pressão.
+pub type ColorField2D<T = f32> = Field2D<T>;
+
+/// Campo de corante com três canais (RGB), advectado pelo `VectorField2D` e desenhado diretamente como cor.
+pub type RgbField2D<T = f32> = Field2D<[T; 3]>;
+
+impl<V: CellValue> Field2D<V> {
+    pub fn new(width: usize, height: usize, initial_value: V) -> Self {
+        let field = Grid2::new(width, height, initial_value);
+        Self { geometry: GridGeometry::UNIT, field }
+    }
+
+    /// Número de colunas da grade.
+    pub fn width(&self) -> usize {
+        self.field.width()
+    }
+
+    /// Número de linhas da grade.
+    pub fn height(&self) -> usize {
+        self.field.height()
+    }
+
+    /// O mesmo campo posicionado no espaço físico por `geometry`.
+    pub fn with_geometry(mut self, geometry: GridGeometry<V::Scalar>) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
+    /// O mesmo campo em outra precisão.
+    pub fn cast<U: Real>(&self) -> Field2D<V::Cast<U>> {
+        Field2D {
+            geometry: self.geometry.cast(),
+            field: self.field.map(|value| value.cast()),
         }
     }
 
-    pub fn bilinear_interpolation(&self, x: f32, y: f32) -> f32 {
-        let x0 = x.floor() as isize;
+    pub fn bilinear_interpolation(&self, x: V::Scalar, y: V::Scalar) -> V {
+        let x0 = x.floor().to_isize();
         let x1 = x0 + 1;
-        let y0 = y.floor() as isize;
+        let y0 = y.floor().to_isize();
         let y1 = y0 + 1;
 
         // Garantir que os índices estejam dentro dos limites
         let clamp = |v: isize, min: isize, max: isize| v.max(min).min(max) as usize;
 
-        let x0 = clamp(x0, 0, self.width as isize - 1);
-        let x1 = clamp(x1, 0, self.width as isize - 1);
-        let y0 = clamp(y0, 0, self.height as isize - 1);
-        let y1 = clamp(y1, 0, self.height as isize - 1);
+        let x0 = clamp(x0, 0, self.width() as isize - 1);
+        let x1 = clamp(x1, 0, self.width() as isize - 1);
+        let y0 = clamp(y0, 0, self.height() as isize - 1);
+        let y1 = clamp(y1, 0, self.height() as isize - 1);
 
-        let q00 = self.field[y0][x0];
-        let q01 = self.field[y0][x1];
-        let q10 = self.field[y1][x0];
-        let q11 = self.field[y1][x1];
+        let (zero, one) = (<V::Scalar as Real>::ZERO, V::Scalar::ONE);
+        let tx = (x - V::Scalar::from_usize(x0)).clamp(zero, one);
+        let ty = (y - V::Scalar::from_usize(y0)).clamp(zero, one);
 
-        let tx = x - x0 as f32;
-        let ty = y - y0 as f32;
+        let a = V::lerp(self.field[y0][x0], self.field[y0][x1], tx);
+        let b = V::lerp(self.field[y1][x0], self.field[y1][x1], tx);
+        V::lerp(a, b, ty)
+    }
 
-        let a = q00 * (1.0 - tx) + q01 * tx;
-        let b = q10 * (1.0 - tx) + q11 * tx;
+    /// Valor da célula `(x, y)`, se ela existir.
+    pub fn get(&self, x: usize, y: usize) -> Option<V> {
+        self.field.get(x, y).copied()
+    }
+
+    /// Define o valor da célula `(x, y)`; falha fora da grade.
+    pub fn set(&mut self, x: usize, y: usize, value: V) -> Result<(), OutOfBounds> {
+        self.field.set(x, y, value)
+    }
 
-        a * (1.0 - ty) + b * ty
+    /// Valor interpolado numa posição em metros.
+    pub fn sample(&self, position: [V::Scalar; 2]) -> V {
+        let [x, y] = self.geometry.index(position);
+        self.bilinear_interpolation(x, y)
     }
 
-    pub fn update(&mut self, velocity_field: &VectorField2D, delta_time: f32) -> Self {
+    /// Advecção semi-Lagrangiana: cada célula busca o valor no ponto de onde o escoamento veio.
+    pub fn update(&self, velocity_field: &VectorField2D<V::Scalar>, delta_time: V::Scalar) -> Self {
         let mut new_field = self.field.clone();
+        let [dx, dy] = self.geometry.spacing;
 
-        for y in 0..self.height {
-            for x in 0..self.width {
+        new_field.par_rows_mut(|y, row| {
+            for (x, value) in row.iter_mut().enumerate() {
                 let velocity = velocity_field.field[y][x];
-                let px = x as f32 - velocity[0] * delta_time;
-                let py = y as f32 - velocity[1] * delta_time;
+                let px = V::Scalar::from_usize(x) - velocity[0] * delta_time / dx;
+                let py = V::Scalar::from_usize(y) - velocity[1] * delta_time / dy;
 
-                new_field[y][x] = self.bilinear_interpolation(px, py);
+                *value = self.bilinear_interpolation(px, py);
+            }
+        });
+
+        Self { geometry: self.geometry, field: new_field }
+    }
+
+    /// Adiciona `amount` às células cujo centro está dentro de um círculo, com queda linear
+    /// até a borda. Centro e raio são dados em metros. Onde círculos de tintas diferentes se
+    /// encontram, elas se somam e se misturam.
+    pub fn splat(&mut self, position: [V::Scalar; 2], radius: V::Scalar, amount: V) {
+        let [x, y] = self.geometry.index(position);
+        let [dx, dy] = self.geometry.spacing;
+        for (cy, row) in self.field.rows_mut().enumerate() {
+            for (cx, value) in row.iter_mut().enumerate() {
+                let offset_x = (V::Scalar::from_usize(cx) - x) * dx;
+                let offset_y = (V::Scalar::from_usize(cy) - y) * dy;
+                let distance = (offset_x * offset_x + offset_y * offset_y).sqrt();
+                if distance < radius {
+                    *value = value.add_scaled(amount, V::Scalar::ONE - distance / radius);
+                }
             }
         }
+    }
+
+    /// Decaimento exponencial de todos os componentes.
+    pub fn dissipate(&mut self, rate: V::Scalar, delta_time: V::Scalar) {
+        let factor = (-rate * delta_time).exp();
+        for value in self.field.cells_mut() {
+            *value = value.scale(factor);
+        }
+    }
 
+    /// Reamostra o campo para `width` x `height` células cobrindo a mesma área. Cada nova
+    /// célula recebe a média das antigas ponderada pela área de sobreposição, então a massa
+    /// total (valor vezes área da célula) é conservada exatamente. Para velocidades, isso
+    /// mantém o momento total; o resultado só é livre de divergência na nova grade depois de
+    /// uma projeção, e `FluidSolver::resample` faz as duas coisas.
+    pub fn resample(&self, width: usize, height: usize) -> Self {
         Self {
-            width: self.width,
-            height: self.height,
-            field: new_field,
+            geometry: self.geometry.resized(self.width(), self.height(), width, height),
+            field: resample_conservative(&self.field, width, height),
         }
     }
 }
 
-impl VectorField2D {
-
-    pub fn onMouseClick(&self, x: i16, y: i16, deltaX: i16, deltaY: i16) {
-        // É necessário ver como chamar e como passar referencia de field.
-        /*
-        let magnitude = 10.0;
-        let screenRatioX = 40.0 / 800.0;
-        let screenRatioY = 40.0 / 600.0;
-
-        let xPos = (x as f32 * screenRatioX).round();
-        let yPos = (y as f32 * screenRatioY).round();
+impl<T: Real> RgbField2D<T> {
+    /// Soma dos três canais em cada célula, como concentração total de tinta.
+    pub fn total(&self) -> ColorField2D<T> {
+        Field2D { geometry: self.geometry, field: self.field.map(|value| value.iter().copied().sum()) }
+    }
+}
 
-        for (i, row) in self.field.iter_mut().enumerate() {
-            for (j, value) in row.iter_mut().enumerate() {
-                let cellX = i as f32;
-                let cellY = j as f32;
-                let distance = ((xPos - cellX).powi(2) + (yPos - cellY).powi(2)).sqrt();
+impl<T: Real> VectorField2D<T> {
+    /// Vorticidade `dv/dx - du/dy` por diferenças centrais (laterais nas bordas).
+    pub fn curl(&self, x: usize, y: usize) -> T {
+        let (left, right) = (x.saturating_sub(1), (x + 1).min(self.width() - 1));
+        let (up, down) = (y.saturating_sub(1), (y + 1).min(self.height() - 1));
 
-                let influence = 1.0 / (distance + 1.0);
+        let dvdx = (self.field[y][right][1] - self.field[y][left][1]) / (T::from_usize((right - left).max(1)) * self.geometry.spacing[0]);
+        let dudy = (self.field[down][x][0] - self.field[up][x][0]) / (T::from_usize((down - up).max(1)) * self.geometry.spacing[1]);
+        dvdx - dudy
+    }
 
-                let deltaMagnitudeX = (deltaX as f32) * influence * magnitude;
-                let deltaMagnitudeY = (deltaY as f32) * influence * magnitude;
+    /// Auto-advecção semi-Lagrangiana: cada célula busca a velocidade no ponto de onde veio.
+    pub fn advect(&self, delta_time: T) -> Self {
+        self.update(self, delta_time)
+    }
+}
 
-                let x_val = value[0];
-                let y_val = value[1];
-                let new_x_val = x_val + deltaMagnitudeX;
-                let new_y_val = y_val + deltaMagnitudeY;
+/// Para cada nova célula ao longo de um eixo, as células antigas que ela cobre e a fração
+/// da nova célula coberta por cada uma.
+fn overlap_weights(old: usize, new: usize) -> Vec<Vec<(usize, f64)>> {
+    let ratio = old as f64 / new as f64;
+    (0..new)
+        .map(|j| {
+            let (start, end) = (j as f64 * ratio, (j + 1) as f64 * ratio);
+            (start.floor() as usize..(end.ceil() as usize).min(old))
+                .filter_map(|i| {
+                    let overlap = end.min((i + 1) as f64) - start.max(i as f64);
+                    (overlap > 0.0).then_some((i, overlap / ratio))
+                })
+                .collect()
+        })
+        .collect()
+}
 
-                // Usando o método set para alterar o valor
-                if let Err(e) = self.set(i, j, [new_x_val, new_y_val]) {
-                    println!("Erro ao definir valor: {}", e);
-                }
+/// Média das células antigas sob cada nova célula, ponderada pela área de sobreposição.
+fn resample_conservative<V: CellValue>(field: &Grid2<V>, width: usize, height: usize) -> Grid2<V> {
+    let columns = overlap_weights(field.width(), width);
+    let rows = overlap_weights(field.height(), height);
 
-                println!("Nova posição: ({}, {}), valor alterado: ({}, {})", i, j, new_x_val, new_y_val);
+    Grid2::from_fn(width, height, |x, y| {
+        let mut total = V::ZERO;
+        for &(old_y, weight_y) in &rows[y] {
+            for &(old_x, weight_x) in &columns[x] {
+                total = total.add_scaled(field[old_y][old_x], V::Scalar::from_f64(weight_x * weight_y));
             }
         }
-        */
+        total
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cast_round_trip_is_exact() {
+        let geometry = GridGeometry::new([0.25, -1.5], [0.1, 0.2]);
+        let velocity_field = VectorField2D {
+            geometry,
+            field: Grid2::from_fn(5, 4, |x, y| [0.1 * x as f32 - 0.3, 1.0 / (1.0 + y as f32)]),
+        };
+
+        let wide: VectorField2D<f64> = velocity_field.cast();
+        assert_eq!(wide.geometry.spacing, [0.1f32 as f64, 0.2f32 as f64]);
+        assert_eq!(wide.field[3][4], [0.1f32 * 4.0 - 0.3, 1.0 / 4.0].map(f64::from));
+        assert_eq!(wide.cast::<f32>().field, velocity_field.field);
+        assert_eq!(wide.cast::<f32>().geometry, velocity_field.geometry);
     }
 
+    #[test]
+    fn resample_keeps_the_mass_of_every_dye() {
+        let mut dye = RgbField2D::<f64>::new(24, 16, [0.0; 3]);
+        dye.splat([6.0, 5.0], 4.0, [1.0, 0.0, 0.0]);
+        dye.splat([9.0, 8.0], 5.0, [0.0, 0.5, 0.25]);
+        let mass = |dye: &RgbField2D<f64>| {
+            let area = dye.geometry.cell_area();
+            [0, 1, 2].map(|c| dye.field.cells().map(|value| value[c] * area).sum::<f64>())
+        };
 
-}
\ No newline at end of file
+        for (width, height) in [(17, 11), (48, 32), (5, 3)] {
+            let resampled = dye.resample(width, height);
+            for (before, after) in mass(&dye).into_iter().zip(mass(&resampled)) {
+                assert!((before - after).abs() < 1e-9 * before.max(1.0), "{before} -> {after} on {width}x{height}");
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+use std::iter::StepBy;
+use std::ops::{Index, IndexMut};
+use std::slice::{ChunksExact, ChunksExactMut, Iter, IterMut};
+use rayon::prelude::*;
+
+use super::real::Real;
+
+/// Where a grid sits in the physical world: the position of its first cell and the size of
+/// its cells, in meters. Fields carry one, so velocities are in meters per second and every
+/// derivative, advection and diagnostic accounts for the cell size. Positions follow the
+/// grid's own axes, with `y` growing along the rows (downwards on screen). The geometry is
+/// kept in the precision `T` of the field it places, so `f64` runs get `f64` cell sizes.
+///
+/// The default `GridGeometry::UNIT` has one-meter cells starting at the origin, so cell
+/// indices, positions and velocities in cells per second all coincide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridGeometry<T = f32> {
+    /// Position of the center of cell `(0, 0)`, in meters.
+    pub origin: [T; 2],
+    /// Width and height of a cell, in meters.
+    pub spacing: [T; 2],
+}
+
+impl<T: Real> Default for GridGeometry<T> {
+    fn default() -> Self {
+        Self::UNIT
+    }
+}
+
+impl<T: Real> GridGeometry<T> {
+    pub const UNIT: Self = Self { origin: [T::ZERO; 2], spacing: [T::ONE; 2] };
+
+    pub fn new(origin: [T; 2], spacing: [T; 2]) -> Self {
+        Self { origin, spacing }
+    }
+
+    /// Square cells of `cell_size` meters, with cell `(0, 0)` centered on the origin.
+    pub fn uniform(cell_size: T) -> Self {
+        Self::new([T::ZERO; 2], [cell_size, cell_size])
+    }
+
+    /// Grid of `width` x `height` cells that exactly covers `size` meters.
+    pub fn covering(width: usize, height: usize, size: [T; 2]) -> Self {
+        let spacing = [size[0] / T::from_usize(width), size[1] / T::from_usize(height)];
+        let half = T::from_f32(0.5);
+        Self::new([half * spacing[0], half * spacing[1]], spacing)
+    }
+
+    /// The same geometry in another precision.
+    pub fn cast<U: Real>(&self) -> GridGeometry<U> {
+        let cast = |value: [T; 2]| value.map(|c| U::from_f64(c.to_f64()));
+        GridGeometry::new(cast(self.origin), cast(self.spacing))
+    }
+
+    /// Geometry of a `new_width` x `new_height` grid covering the same area as this one does
+    /// with `width` x `height` cells.
+    pub fn resized(&self, width: usize, height: usize, new_width: usize, new_height: usize) -> Self {
+        let spacing = [
+            self.spacing[0] * T::from_usize(width) / T::from_usize(new_width),
+            self.spacing[1] * T::from_usize(height) / T::from_usize(new_height),
+        ];
+        let half = T::from_f32(0.5);
+        let origin = [
+            self.origin[0] + half * (spacing[0] - self.spacing[0]),
+            self.origin[1] + half * (spacing[1] - self.spacing[1]),
+        ];
+        Self::new(origin, spacing)
+    }
+
+    /// Position in meters of a point given in (fractional) cell indices.
+    pub fn position(&self, x: T, y: T) -> [T; 2] {
+        [self.origin[0] + x * self.spacing[0], self.origin[1] + y * self.spacing[1]]
+    }
+
+    /// Fractional cell indices of a position in meters.
+    pub fn index(&self, position: [T; 2]) -> [T; 2] {
+        [(position[0] - self.origin[0]) / self.spacing[0], (position[1] - self.origin[1]) / self.spacing[1]]
+    }
+
+    /// Velocity in meters per second of a displacement rate given in cells per second.
+    pub fn physical_velocity(&self, velocity: [T; 2]) -> [T; 2] {
+        [velocity[0] * self.spacing[0], velocity[1] * self.spacing[1]]
+    }
+
+    /// Displacement rate in cells per second of a velocity in meters per second.
+    pub fn cell_velocity(&self, velocity: [T; 2]) -> [T; 2] {
+        [velocity[0] / self.spacing[0], velocity[1] / self.spacing[1]]
+    }
+
+    /// Area of one cell, in square meters.
+    pub fn cell_area(&self) -> T {
+        self.spacing[0] * self.spacing[1]
+    }
+}
+
+/// Values of a `width` x `height` grid stored row after row in one contiguous buffer, ready
+/// to be handed to SIMD loops or uploaded to the GPU as is. `grid[y]` is row `y` as a slice,
+/// so `grid[y][x]` reads and writes cells like nested vectors do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid2<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+/// A cell position outside the grid, returned by `Grid2::set`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cell ({}, {}) is outside the {}x{} grid", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+impl<T: Clone> Grid2<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self { width, height, data: vec![value; width * height] }
+    }
+
+    /// A grid holding `rows`, which must all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(rows.iter().all(|row| row.len() == width), "all rows of a grid must have the same length");
+        Self { width, height: rows.len(), data: rows.concat() }
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        self.rows().map(<[T]>::to_vec).collect()
+    }
+
+    /// Copies row `source` over row `target`.
+    pub fn copy_row(&mut self, source: usize, target: usize)
+    where
+        T: Copy,
+    {
+        let width = self.width;
+        self.data.copy_within(source * width..(source + 1) * width, target * width);
+    }
+}
+
+impl<T> Grid2<T> {
+    /// A grid from `width * height` values in row order.
+    pub fn from_vec(width: usize, height: usize, data: Vec<T>) -> Self {
+        assert_eq!(data.len(), width * height, "a {width}x{height} grid needs {} values", width * height);
+        Self { width, height, data }
+    }
+
+    /// A grid with `value(x, y)` in every cell.
+    pub fn from_fn(width: usize, height: usize, mut value: impl FnMut(usize, usize) -> T) -> Self {
+        let data = (0..width * height).map(|i| value(i % width, i / width)).collect();
+        Self { width, height, data }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.data[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        (x < self.width && y < self.height).then(|| &mut self.data[y * self.width + x])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: T) -> Result<(), OutOfBounds> {
+        let (width, height) = (self.width, self.height);
+        let cell = self.get_mut(x, y).ok_or(OutOfBounds { x, y, width, height })?;
+        *cell = value;
+        Ok(())
+    }
+
+    /// Every cell, row after row.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Every cell, row after row.
+    pub fn cells(&self) -> Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn cells_mut(&mut self) -> IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> ChunksExact<'_, T> {
+        self.data.chunks_exact(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> ChunksExactMut<'_, T> {
+        self.data.chunks_exact_mut(self.width.max(1))
+    }
+
+    /// The cells of column `x`, from top to bottom.
+    pub fn column(&self, x: usize) -> StepBy<Iter<'_, T>> {
+        assert!(x < self.width, "column {x} is outside a grid {} cells wide", self.width);
+        self.data[x..].iter().step_by(self.width)
+    }
+
+    pub fn column_mut(&mut self, x: usize) -> StepBy<IterMut<'_, T>> {
+        assert!(x < self.width, "column {x} is outside a grid {} cells wide", self.width);
+        self.data[x..].iter_mut().step_by(self.width)
+    }
+
+    /// The same grid with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid2<U> {
+        Grid2 { width: self.width, height: self.height, data: self.data.iter().map(f).collect() }
+    }
+}
+
+/// Grids with fewer cells than this are swept on the calling thread: handing them to the
+/// thread pool costs more than the work itself.
+const PARALLEL_THRESHOLD: usize = 128 * 128;
+
+impl<T: Send> Grid2<T> {
+    /// Calls `f(y, row)` on every row, spread over rayon's thread pool for grids of at least
+    /// `PARALLEL_THRESHOLD` cells and on the calling thread for smaller ones.
+    pub fn par_rows_mut(&mut self, f: impl Fn(usize, &mut [T]) + Sync) {
+        if self.data.len() < PARALLEL_THRESHOLD {
+            self.rows_mut().enumerate().for_each(|(y, row)| f(y, row));
+            return;
+        }
+        let width = self.width.max(1);
+        self.data.par_chunks_exact_mut(width).enumerate().for_each(|(y, row)| f(y, row));
+    }
+}
+
+impl<T: Sync> Grid2<T> {
+    /// Calls `f(y, row)` on every row, in parallel under the same conditions as
+    /// `par_rows_mut`, and collects the results in row order.
+    pub fn par_rows<U: Send>(&self, f: impl Fn(usize, &[T]) -> U + Sync) -> Vec<U> {
+        if self.data.len() < PARALLEL_THRESHOLD {
+            return self.rows().enumerate().map(|(y, row)| f(y, row)).collect();
+        }
+        let width = self.width.max(1);
+        self.data.par_chunks_exact(width).enumerate().map(|(y, row)| f(y, row)).collect()
+    }
+}
+
+impl<T> Index<usize> for Grid2<T> {
+    type Output = [T];
+
+    fn index(&self, y: usize) -> &[T] {
+        self.row(y)
+    }
+}
+
+impl<T> IndexMut<usize> for Grid2<T> {
+    fn index_mut(&mut self, y: usize) -> &mut [T] {
+        self.row_mut(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set_outside_the_grid() {
+        let mut grid = Grid2::new(3, 2, 0);
+        assert_eq!(grid.set(2, 1, 7), Ok(()));
+        assert_eq!(grid.get(2, 1), Some(&7));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(grid.set(3, 1, 1), Err(OutOfBounds { x: 3, y: 1, width: 3, height: 2 }));
+        assert_eq!(grid.set(0, 2, 1), Err(OutOfBounds { x: 0, y: 2, width: 3, height: 2 }));
+        assert_eq!(grid.as_slice(), &[0, 0, 0, 0, 0, 7]);
+    }
+
+    #[test]
+    fn rows_and_vectors_round_trip() {
+        let rows = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let grid = Grid2::from_rows(rows.clone());
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[1][0], 4);
+        assert_eq!(grid.to_rows(), rows);
+        assert_eq!(grid, Grid2::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]));
+        assert_eq!(grid, Grid2::from_fn(3, 2, |x, y| 3 * y + x + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn ragged_rows_are_rejected() {
+        Grid2::from_rows(vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 6 values")]
+    fn short_vectors_are_rejected() {
+        Grid2::from_vec(3, 2, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn columns_step_over_rows() {
+        let mut grid = Grid2::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        for value in grid.column_mut(2) {
+            *value *= 10;
+        }
+        assert_eq!(grid.to_rows(), vec![vec![1, 2, 30], vec![4, 5, 60]]);
+    }
+
+    #[test]
+    fn parallel_rows_see_every_row_in_order() {
+        // Large enough to go through the thread pool.
+        let mut grid = Grid2::new(200, 100, 0);
+        grid.par_rows_mut(|y, row| row.iter_mut().enumerate().for_each(|(x, value)| *value = y * 1000 + x));
+        assert_eq!(grid.get(199, 99), Some(&99_199));
+        let sums = grid.par_rows(|y, row| (y, row.len()));
+        assert_eq!(sums, (0..100).map(|y| (y, 200)).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,223 @@
+use super::field::{ColorField2D, VectorField2D};
+use super::grid::Grid2;
+use super::real::Real;
+
+/// Thermal condition on one edge of the domain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThermalWall<T = f32> {
+    /// No heat crosses the wall.
+    Insulated,
+    /// The wall is held at a fixed temperature.
+    Fixed(T),
+}
+
+/// Temperature carried by the flow and conducted through both fluid and solid cells
+/// (conjugate heat transfer). Each cell has its own conductivity and volumetric heat
+/// capacity, so a metal heat sink inside the flow conducts heat much faster than the air around it.
+#[derive(Debug, Clone)]
+pub struct HeatTransfer<T: Real = f32> {
+    pub width: usize,
+    pub height: usize,
+    pub temperature: ColorField2D<T>,
+    pub conductivity: ColorField2D<T>,
+    pub heat_capacity: ColorField2D<T>,
+    /// Heat generated per unit time in each cell, e.g. by a chip under a heat sink.
+    pub heat_source: ColorField2D<T>,
+    /// Cells pinned to a fixed temperature.
+    pub fixed_temperature: Grid2<Option<T>>,
+    /// Left, right, top and bottom edges.
+    pub walls: [ThermalWall<T>; 4],
+    /// Buoyancy per degree above `reference_temperature`, applied to the fluid velocity.
+    pub expansion: T,
+    pub reference_temperature: T,
+    pub iterations: usize,
+}
+
+impl<T: Real> HeatTransfer<T> {
+    pub fn new(width: usize, height: usize, temperature: T, conductivity: T) -> Self {
+        Self {
+            width,
+            height,
+            temperature: ColorField2D::new(width, height, temperature),
+            conductivity: ColorField2D::new(width, height, conductivity),
+            heat_capacity: ColorField2D::new(width, height, T::ONE),
+            heat_source: ColorField2D::new(width, height, T::ZERO),
+            fixed_temperature: Grid2::new(width, height, None),
+            walls: [ThermalWall::Insulated; 4],
+            expansion: T::ZERO,
+            reference_temperature: temperature,
+            iterations: 30,
+        }
+    }
+
+    /// Gives the cells where `mask` is set the properties of a solid material.
+    pub fn set_material(&mut self, mask: &Grid2<bool>, conductivity: T, heat_capacity: T) {
+        for (y, mask_row) in mask.rows().enumerate() {
+            for (x, &masked) in mask_row.iter().enumerate() {
+                if masked {
+                    self.conductivity.field[y][x] = conductivity;
+                    self.heat_capacity.field[y][x] = heat_capacity;
+                }
+            }
+        }
+    }
+
+    /// Carries the temperature and material properties over to a `width` x `height` grid
+    /// covering the same area. Pinned temperatures follow the nearest old cell.
+    pub fn resample(&mut self, width: usize, height: usize) {
+        let fixed_temperature = &self.fixed_temperature;
+        let nearest = |x: usize, old: usize, new: usize| ((x as f32 + 0.5) * old as f32 / new as f32) as usize;
+        self.fixed_temperature = Grid2::from_fn(width, height, |x, y| fixed_temperature[nearest(y, self.height, height)][nearest(x, self.width, width)]);
+
+        self.temperature = self.temperature.resample(width, height);
+        self.conductivity = self.conductivity.resample(width, height);
+        self.heat_capacity = self.heat_capacity.resample(width, height);
+        self.heat_source = self.heat_source.resample(width, height);
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Advects the temperature in fluid cells, conducts heat everywhere and applies buoyancy.
+    /// `solid` marks cells that do not move with the flow.
+    pub fn update(&mut self, velocity_field: &mut VectorField2D<T>, solid: &Grid2<bool>, delta_time: T) {
+        let advected = self.temperature.update(velocity_field, delta_time);
+        for (y, solid_row) in solid.rows().enumerate() {
+            for (x, &solid) in solid_row.iter().enumerate() {
+                if !solid {
+                    self.temperature.field[y][x] = advected.field[y][x];
+                }
+                self.temperature.field[y][x] += self.heat_source.field[y][x] * delta_time / self.heat_capacity.field[y][x];
+            }
+        }
+
+        self.conduct(delta_time);
+
+        if self.expansion != T::ZERO {
+            for (y, solid_row) in solid.rows().enumerate() {
+                for (x, &solid) in solid_row.iter().enumerate() {
+                    if !solid {
+                        let lift = self.expansion * (self.temperature.field[y][x] - self.reference_temperature);
+                        velocity_field.field[y][x][1] -= lift * delta_time;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Implicit conduction with harmonic-mean conductivity between neighbouring cells,
+    /// so heat flux stays continuous across a fluid/solid interface. Cell sizes come from
+    /// the geometry of the temperature field.
+    pub fn conduct(&mut self, delta_time: T) {
+        let (width, height) = (self.width, self.height);
+        let [dx, dy] = self.temperature.geometry.spacing;
+        let initial = self.temperature.field.clone();
+        let conductivity = &self.conductivity.field;
+
+        let two = T::from_f32(2.0);
+        let face = |a: T, b: T| if a + b > T::ZERO { two * a * b / (a + b) } else { T::ZERO };
+
+        for _ in 0..self.iterations {
+            let temperature = &mut self.temperature.field;
+            for y in 0..height {
+                for x in 0..width {
+                    if let Some(fixed) = self.fixed_temperature[y][x] {
+                        temperature[y][x] = fixed;
+                        continue;
+                    }
+
+                    let capacity = self.heat_capacity.field[y][x];
+                    let mut weight = T::ZERO;
+                    let mut flux = T::ZERO;
+
+                    // Each neighbour with the wall beyond it and the spacing between centers.
+                    let neighbours = [
+                        (x.checked_sub(1).map(|nx| (nx, y)), self.walls[0], dx),
+                        ((x + 1 < width).then_some((x + 1, y)), self.walls[1], dx),
+                        (y.checked_sub(1).map(|ny| (x, ny)), self.walls[2], dy),
+                        ((y + 1 < height).then_some((x, y + 1)), self.walls[3], dy),
+                    ];
+                    for (neighbour, wall, spacing) in neighbours {
+                        let scale = delta_time / (capacity * spacing * spacing);
+                        match (neighbour, wall) {
+                            (Some((nx, ny)), _) => {
+                                let k = face(conductivity[y][x], conductivity[ny][nx]) * scale;
+                                weight += k;
+                                flux += k * temperature[ny][nx];
+                            }
+                            // The wall sits half a cell away from the cell center.
+                            (None, ThermalWall::Fixed(wall_temperature)) => {
+                                let k = two * conductivity[y][x] * scale;
+                                weight += k;
+                                flux += k * wall_temperature;
+                            }
+                            (None, ThermalWall::Insulated) => (),
+                        }
+                    }
+
+                    temperature[y][x] = (initial[y][x] + flux) / (T::ONE + weight);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Total heat and mean squared distance from the middle of the box of 16 one-meter cells.
+    fn heat_and_spread(heat: &HeatTransfer<f64>) -> (f64, f64) {
+        let geometry = heat.temperature.geometry;
+        let (mut total, mut moment) = (0.0, 0.0);
+        for (y, row) in heat.temperature.field.rows().enumerate() {
+            for (x, &temperature) in row.iter().enumerate() {
+                let [px, py] = geometry.position(x as f64, y as f64);
+                let energy = temperature * geometry.cell_area();
+                total += energy;
+                moment += energy * ((px - 7.5).powi(2) + (py - 7.5).powi(2));
+            }
+        }
+        (total, moment / total)
+    }
+
+    #[test]
+    fn conduction_spreads_alike_after_resampling() {
+        let mut coarse = HeatTransfer::new(16, 16, 0.0, 1.0);
+        coarse.temperature.field = Grid2::from_fn(16, 16, |x, y| if (6..10).contains(&x) && (6..10).contains(&y) { 1.0 } else { 0.0 });
+        let mut fine = coarse.clone();
+        fine.resample(32, 32);
+
+        let (initial_heat, initial_spread) = heat_and_spread(&coarse);
+        for _ in 0..20 {
+            coarse.conduct(0.05);
+            fine.conduct(0.05);
+        }
+        // In two dimensions the mean squared distance grows by 4 k t.
+        for heat in [&coarse, &fine] {
+            let (total, spread) = heat_and_spread(heat);
+            assert!((total - initial_heat).abs() < 1e-9, "heat {total} instead of {initial_heat}");
+            let growth = spread - initial_spread;
+            assert!((growth - 4.0).abs() < 0.4, "spread grew by {growth} on {} cells", heat.width);
+        }
+    }
+
+    #[test]
+    fn steady_flux_through_two_materials_matches_series_resistance() {
+        // Air on the left half, metal ten times as conductive on the right, between walls at 1 and 0.
+        let mut heat = HeatTransfer::<f64>::new(16, 1, 0.0, 1.0);
+        heat.set_material(&Grid2::from_fn(16, 1, |x, _| x >= 8), 10.0, 1.0);
+        heat.walls[0] = ThermalWall::Fixed(1.0);
+        heat.walls[1] = ThermalWall::Fixed(0.0);
+        heat.iterations = 5000;
+        heat.conduct(1e9);
+
+        // The flux crosses resistances of 8 / 1 and 8 / 10 in series.
+        let flux = 1.0 / (8.0 + 0.8);
+        for x in 0..16 {
+            let center = x as f64 + 0.5;
+            let expected = if x < 8 { 1.0 - flux * center } else { flux * (16.0 - center) / 10.0 };
+            let temperature = heat.temperature.field[0][x];
+            assert!((temperature - expected).abs() < 1e-6, "{temperature} instead of {expected} at {x}");
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use obj::{Obj, ObjData, ObjError};
+
+use super::grid::Grid2;
+use super::sdf::Sdf;
+
+/// Plane used to cut a mesh, given by a point on it and its normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub origin: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+impl Plane {
+    pub fn new(origin: [f32; 3], normal: [f32; 3]) -> Self {
+        Self { origin, normal: normalize(normal) }
+    }
+
+    /// The plane `z = depth`, seen from the front: slices come out as side views.
+    pub fn z(depth: f32) -> Self {
+        Self::new([0.0, 0.0, depth], [0.0, 0.0, 1.0])
+    }
+
+    /// In-plane axes `(u, v)`, with `v` as close to the world `y` axis as the plane allows, so
+    /// vertical slices keep "up" pointing up.
+    fn basis(&self) -> ([f32; 3], [f32; 3]) {
+        let up = if self.normal[1].abs() > 0.99 { [0.0, 0.0, -1.0] } else { [0.0, 1.0, 0.0] };
+        let along = dot(up, self.normal);
+        let v = normalize([up[0] - along * self.normal[0], up[1] - along * self.normal[1], up[2] - along * self.normal[2]]);
+        (cross(v, self.normal), v)
+    }
+}
+
+/// Triangle soup read from a Wavefront OBJ file. Faces with more than three vertices are
+/// split into fans, so they should be convex.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub triangles: Vec<[[f32; 3]; 3]>,
+}
+
+impl Mesh {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        Ok(Self::from_obj(&Obj::load(path)?.data))
+    }
+
+    /// Reads OBJ source already in memory, such as a mesh bundled with `include_str!`.
+    pub fn parse(source: &str) -> Result<Self, ObjError> {
+        Ok(Self::from_obj(&ObjData::load_buf(source.as_bytes())?))
+    }
+
+    pub fn from_obj(data: &ObjData) -> Self {
+        let mut triangles = Vec::new();
+        let polygons = data.objects.iter().flat_map(|object| &object.groups).flat_map(|group| &group.polys);
+        for polygon in polygons {
+            let corners: Vec<[f32; 3]> = polygon.0.iter().map(|index| data.position[index.0]).collect();
+            for i in 1..corners.len().saturating_sub(1) {
+                triangles.push([corners[0], corners[i], corners[i + 1]]);
+            }
+        }
+        Self { triangles }
+    }
+
+    /// Smallest and largest corner of the axis-aligned bounding box.
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for vertex in self.triangles.iter().flatten() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+        (min, max)
+    }
+
+    /// Cross-section of the mesh by `plane`, as closed loops in the plane's own coordinates.
+    /// A closed (watertight) mesh gives closed loops; open ends of a broken mesh are dropped.
+    pub fn slice(&self, plane: &Plane) -> Vec<Vec<[f32; 2]>> {
+        let (u, v) = plane.basis();
+        let project = |p: [f32; 3]| {
+            let offset = sub(p, plane.origin);
+            [dot(offset, u), dot(offset, v)]
+        };
+        // Vertices exactly on the plane count as above it, so every crossing is seen once.
+        let side = |p: [f32; 3]| dot(sub(p, plane.origin), plane.normal);
+
+        let mut segments = Vec::new();
+        for triangle in &self.triangles {
+            let mut crossings = Vec::with_capacity(2);
+            for i in 0..3 {
+                // Order each edge the same way in both triangles sharing it, so the crossing
+                // point is bit-for-bit identical and the loops join up.
+                let (mut a, mut b) = (triangle[i], triangle[(i + 1) % 3]);
+                if a > b {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                let (side_a, side_b) = (side(a), side(b));
+                if (side_a >= 0.0) != (side_b >= 0.0) {
+                    let crossing = if side_b == 0.0 {
+                        b
+                    } else {
+                        let t = side_a / (side_a - side_b);
+                        [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+                    };
+                    crossings.push(project(crossing));
+                }
+            }
+            if let [start, end] = crossings[..] {
+                segments.push([start, end]);
+            }
+        }
+
+        join_segments(&segments)
+    }
+
+    /// Cross-section by `plane` scaled to fit `size` cells along its longer side and centered
+    /// on `center`, in grid coordinates (rows grow downwards), ready for `FluidSolver::add_obstacle`.
+    /// Returns `None` when the plane misses the mesh.
+    pub fn slice_to_sdf(&self, plane: &Plane, center: [f32; 2], size: f32) -> Option<Sdf> {
+        let loops = self.slice(plane);
+        let mut min = [f32::MAX; 2];
+        let mut max = [f32::MIN; 2];
+        for point in loops.iter().flatten() {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(point[axis]);
+                max[axis] = max[axis].max(point[axis]);
+            }
+        }
+        let extent = (max[0] - min[0]).max(max[1] - min[1]);
+        if loops.is_empty() || extent <= 0.0 {
+            return None;
+        }
+
+        let scale = size / extent;
+        let middle = [(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5];
+        let placed = loops
+            .into_iter()
+            .map(|points| {
+                points
+                    .into_iter()
+                    .map(|[u, v]| [center[0] + (u - middle[0]) * scale, center[1] - (v - middle[1]) * scale])
+                    .collect()
+            })
+            .collect();
+        Some(Sdf::region(placed))
+    }
+
+    /// Fills the inside of the mesh on a voxel grid with `resolution` voxels along the longest
+    /// side of its bounding box. Each column of voxels casts a ray along `z` and toggles
+    /// between outside and inside at every surface crossing, so the mesh must be closed.
+    pub fn voxelize(&self, resolution: usize) -> VoxelGrid {
+        let (min, max) = self.bounds();
+        let extent = [max[0] - min[0], max[1] - min[1], max[2] - min[2]];
+        let voxel_size = extent[0].max(extent[1]).max(extent[2]) / resolution as f32;
+        let cells = |axis: usize| ((extent[axis] / voxel_size).ceil() as usize).max(1);
+        let mut grid = VoxelGrid::new(cells(0), cells(1), cells(2), min, voxel_size);
+
+        // A tiny offset keeps rays off the shared edges of neighbouring triangles.
+        let jitter = voxel_size * 1e-4;
+        let mut crossings: Grid2<Vec<f32>> = Grid2::new(grid.width, grid.height, Vec::new());
+        for triangle in &self.triangles {
+            let column = |value: f32, axis: usize| ((value - min[axis]) / voxel_size - 0.5).max(0.0);
+            let range = |axis: usize, limit: usize| {
+                let low = triangle.iter().map(|p| p[axis]).fold(f32::MAX, f32::min);
+                let high = triangle.iter().map(|p| p[axis]).fold(f32::MIN, f32::max);
+                (column(low, axis).ceil() as usize)..((column(high, axis).floor() as usize + 1).min(limit))
+            };
+            for y in range(1, grid.height) {
+                for x in range(0, grid.width) {
+                    let point = [min[0] + (x as f32 + 0.5) * voxel_size + jitter, min[1] + (y as f32 + 0.5) * voxel_size + jitter * 0.7];
+                    if let Some(z) = ray_z_crossing(triangle, point) {
+                        crossings[y][x].push(z);
+                    }
+                }
+            }
+        }
+
+        for (y, row) in crossings.rows_mut().enumerate() {
+            for (x, hits) in row.iter_mut().enumerate() {
+                hits.sort_by(f32::total_cmp);
+                for pair in hits.chunks_exact(2) {
+                    for z in 0..grid.depth {
+                        let center = min[2] + (z as f32 + 0.5) * voxel_size;
+                        if center > pair[0] && center < pair[1] {
+                            grid.cells[z][y][x] = true;
+                        }
+                    }
+                }
+            }
+        }
+        grid
+    }
+}
+
+/// Occupancy of a box-shaped block of cubic voxels, one grid per layer of constant `z`,
+/// indexed `[z][y][x]` along the mesh axes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// Mesh coordinates of the corner of voxel `(0, 0, 0)`.
+    pub origin: [f32; 3],
+    pub voxel_size: f32,
+    pub cells: Vec<Grid2<bool>>,
+}
+
+impl VoxelGrid {
+    pub fn new(width: usize, height: usize, depth: usize, origin: [f32; 3], voxel_size: f32) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+            origin,
+            voxel_size,
+            cells: vec![Grid2::new(width, height, false); depth],
+        }
+    }
+
+    pub fn is_solid(&self, x: usize, y: usize, z: usize) -> bool {
+        self.cells[z][y][x]
+    }
+
+    pub fn count(&self) -> usize {
+        self.cells.iter().flat_map(Grid2::cells).filter(|&&solid| solid).count()
+    }
+
+    /// One layer of constant `z` as a mask with the top of the mesh in row 0, the layout
+    /// used by the 2D solvers.
+    pub fn layer(&self, z: usize) -> Grid2<bool> {
+        Grid2::from_fn(self.width, self.height, |x, y| self.cells[z][self.height - 1 - y][x])
+    }
+}
+
+/// Links segments sharing endpoints into closed loops.
+fn join_segments(segments: &[[[f32; 2]; 2]]) -> Vec<Vec<[f32; 2]>> {
+    let key = |p: [f32; 2]| (p[0].to_bits(), p[1].to_bits());
+    let mut touching: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        for point in segment {
+            touching.entry(key(*point)).or_default().push(i);
+        }
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut loops = Vec::new();
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let start = segments[first][0];
+        let mut points = vec![start];
+        let mut end = segments[first][1];
+
+        let closed = loop {
+            if key(end) == key(start) {
+                break true;
+            }
+            points.push(end);
+            let next = touching[&key(end)].iter().copied().find(|&i| !used[i]);
+            let Some(next) = next else {
+                break false;
+            };
+            used[next] = true;
+            let [a, b] = segments[next];
+            end = if key(a) == key(end) { b } else { a };
+        };
+
+        if closed && points.len() >= 3 {
+            loops.push(points);
+        }
+    }
+    loops
+}
+
+/// Depth at which the line parallel to `z` through `(x, y) = point` crosses `triangle`, if it does.
+fn ray_z_crossing(triangle: &[[f32; 3]; 3], point: [f32; 2]) -> Option<f32> {
+    let [a, b, c] = *triangle;
+    let area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+    let weight_b = ((point[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (point[1] - a[1])) / area;
+    let weight_c = ((b[0] - a[0]) * (point[1] - a[1]) - (point[0] - a[0]) * (b[1] - a[1])) / area;
+    let weight_a = 1.0 - weight_b - weight_c;
+    (weight_a >= 0.0 && weight_b >= 0.0 && weight_c >= 0.0).then(|| weight_a * a[2] + weight_b * b[2] + weight_c * c[2])
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    [a[0] / length, a[1] / length, a[2] / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unit cube with quad faces, which `from_obj` splits into triangles.
+    const CUBE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+        f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 3 4 8 7\nf 2 3 7 6\nf 1 5 8 4\n";
+
+    #[test]
+    fn slicing_a_cube_gives_one_closed_square() {
+        let cube = Mesh::parse(CUBE).unwrap();
+        assert_eq!(cube.triangles.len(), 12);
+
+        let loops = cube.slice(&Plane::z(0.4));
+        assert_eq!(loops.len(), 1);
+        let points = &loops[0];
+        let cross = |i: usize| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a[0] * b[1] - b[0] * a[1]
+        };
+        let area = 0.5 * (0..points.len()).map(cross).sum::<f32>();
+        assert!((area.abs() - 1.0).abs() < 1e-5, "area {area}");
+        for point in points {
+            assert!(point.iter().all(|&c| (-1e-5..=1.0 + 1e-5).contains(&c)), "{point:?} off the square");
+        }
+    }
+
+    #[test]
+    fn voxelized_cube_is_full() {
+        let voxels = Mesh::parse(CUBE).unwrap().voxelize(8);
+        assert_eq!(voxels.count(), 8 * 8 * 8);
+    }
+}
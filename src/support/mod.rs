@@ -1,4 +1,3 @@
-
 #![allow(dead_code)]
 use std::num::NonZeroU32;
 use glium::Display;
@@ -14,6 +13,28 @@ use winit::application::ApplicationHandler;
 pub mod camera;
 pub mod mouse;
 pub mod field;
+pub mod grid;
+pub mod real;
+pub mod solver;
+pub mod combustion;
+pub mod curl_noise;
+pub mod reaction_diffusion;
+pub mod level_set;
+pub mod surface_tension;
+pub mod viscosity;
+pub mod turbulence;
+pub mod shallow_water;
+pub mod euler;
+pub mod heat;
+pub mod particles;
+pub mod aerodynamics;
+pub mod sdf;
+pub mod sparse;
+pub mod mesh;
+pub mod nested;
+pub mod probe;
+pub mod taylor_green;
+pub mod diagnostics;
 
 // 800x600
 
@@ -83,23 +104,7 @@ impl<T: ApplicationContext + 'static> ApplicationHandler<()> for App<T> {
                 event_loop.exit()
             },
             glium::winit::event::WindowEvent::CursorMoved { position, .. } => {
-                let position_x: i16 = position.x as i16;
-                let position_y: i16 = position.y as i16;
-
-                Mouse::update_position(position_x, position_y);
-        
-                let (delta_x, delta_y) = Mouse::get_delta();
-                let (x, y) = Mouse::get_position();
-
-                //VectorField2D::onMouseClick(x, y, delta_x, delta_y);
-        
-                println!("Delta mouse position: {}x{}", delta_x, delta_y);
-                println!("Mouse position: {}x{}", x, y);
-            },
-            glium::winit::event::WindowEvent::MouseInput { state, button, .. } => {
-                if state == glium::winit::event::ElementState::Pressed {
-                    println!("Mouse clicked: {:?}", button);
-                }
+                Mouse::update_position(position.x as i16, position.y as i16);
             },
             ev => {
                 if let Some(state) = &mut self.state {
@@ -139,13 +144,13 @@ impl<T: ApplicationContext + 'static> State<T> {
             .with_context_api(glutin::context::ContextApi::Gles(None))
             .build(Some(window_handle.into()));
 
-        let not_current_gl_context = Some(unsafe {
+        let not_current_gl_context = unsafe {
             gl_config.display().create_context(&gl_config, &context_attributes).unwrap_or_else(|_| {
                 gl_config.display()
                     .create_context(&gl_config, &fallback_context_attributes)
                     .expect("failed to create context")
             })
-        });
+        };
 
         // Determine our framebuffer size based on the window size, or default to 800x600 if it's invisible
         let (width, height): (u32, u32) = if visible { window.inner_size().into() } else { (800, 600) };
@@ -156,7 +161,7 @@ impl<T: ApplicationContext + 'static> State<T> {
         );
         // Now we can create our surface, use it to make our context current and finally create our display
         let surface = unsafe { gl_config.display().create_window_surface(&gl_config, &attrs).unwrap() };
-        let current_context = not_current_gl_context.unwrap().make_current(&surface).unwrap();
+        let current_context = not_current_gl_context.make_current(&surface).unwrap();
         let display = glium::Display::from_context_surface(current_context, surface).unwrap();
 
         Self::from_display_window(display, window)
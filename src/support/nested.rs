@@ -0,0 +1,284 @@
+use super::field::VectorField2D;
+use super::grid::{Grid2, GridGeometry};
+use super::real::Real;
+use super::sdf::Sdf;
+use super::solver::{EdgeCondition, FluidSolver};
+
+/// One grid of a `NestedGrid`, with its own solver and velocity in the shared physical units.
+#[derive(Debug, Clone)]
+pub struct GridLevel<T: Real = f32> {
+    pub solver: FluidSolver<T>,
+    pub velocity_field: VectorField2D<T>,
+    /// First cell of the parent level covered by this one; `[0, 0]` for the coarsest level.
+    pub offset: [usize; 2],
+    /// Cells of this level along each side of a parent cell; 1 for the coarsest level.
+    pub ratio: usize,
+}
+
+impl<T: Real> GridLevel<T> {
+    pub fn geometry(&self) -> GridGeometry<T> {
+        self.velocity_field.geometry
+    }
+
+    /// Nearest inner cell to `position`, if the position lies on this level's inner cells.
+    pub fn cell(&self, position: [T; 2]) -> Option<(usize, usize)> {
+        let [x, y] = self.geometry().index(position);
+        let (x, y) = (x.round(), y.round());
+        let inside = x >= T::ONE && y >= T::ONE && x <= T::from_usize(self.solver.width - 2) && y <= T::from_usize(self.solver.height - 2);
+        inside.then_some((x.to_isize() as usize, y.to_isize() as usize))
+    }
+}
+
+/// A coarse grid over the whole domain with finer patches nested inside it, each patch
+/// refining a rectangle of the level above it. Patches get their edge cells interpolated
+/// from their parent every step, and hand their solution back by averaging onto the parent
+/// cells they cover. The parent is then projected again around the covered cells, which keep
+/// the averaged velocity, so that the parent's own cells balance the flux the patch hands
+/// back. Each level is divergence free on its own grid, but the composite flow is only so up
+/// to the interpolation error at the patch edges. All levels advance with the same time step.
+///
+/// Obstacles are given in meters and rasterized on every level, patches added later included.
+#[derive(Debug, Clone)]
+pub struct NestedGrid<T: Real = f32> {
+    /// Coarsest level first, each following level nested inside the one before it.
+    pub levels: Vec<GridLevel<T>>,
+    obstacles: Vec<Sdf>,
+}
+
+impl<T: Real> NestedGrid<T> {
+    /// A nesting with `solver` and `velocity_field` as the coarsest level.
+    pub fn new(solver: FluidSolver<T>, velocity_field: VectorField2D<T>) -> Self {
+        Self {
+            levels: vec![GridLevel { solver, velocity_field, offset: [0, 0], ratio: 1 }],
+            obstacles: Vec::new(),
+        }
+    }
+
+    /// Nests a new finest level that refines the `size[0]` x `size[1]` cells of the current
+    /// finest level starting at `offset`, each split into `ratio` x `ratio` cells. The
+    /// covered cells must be inner cells of the parent. The patch copies the parent's
+    /// viscosity, turbulence model and iteration count, and starts from its interpolated
+    /// velocity.
+    pub fn refine(&mut self, offset: [usize; 2], size: [usize; 2], ratio: usize) -> &mut GridLevel<T> {
+        let parent = self.levels.last().expect("a nested grid always has a coarsest level");
+        assert!(
+            offset[0] >= 1 && offset[1] >= 1 && offset[0] + size[0] < parent.solver.width && offset[1] + size[1] < parent.solver.height,
+            "a patch must cover inner cells of its parent"
+        );
+
+        let (width, height) = (size[0] * ratio + 2, size[1] * ratio + 2);
+        let parent_geometry = parent.geometry();
+        let spacing = parent_geometry.spacing.map(|spacing| spacing / T::from_usize(ratio));
+        // The first inner cell of the patch starts on the edge of the first covered parent cell.
+        let half = T::from_f32(0.5);
+        let origin = [
+            parent_geometry.origin[0] + (T::from_usize(offset[0]) - half) * parent_geometry.spacing[0] - half * spacing[0],
+            parent_geometry.origin[1] + (T::from_usize(offset[1]) - half) * parent_geometry.spacing[1] - half * spacing[1],
+        ];
+        let geometry = GridGeometry::new(origin, spacing);
+
+        let mut solver = FluidSolver::new(width, height);
+        solver.viscosity = parent.solver.viscosity;
+        solver.turbulence = parent.solver.turbulence;
+        solver.iterations = parent.solver.iterations;
+        solver.edges = [EdgeCondition::Fixed; 4];
+        for obstacle in &self.obstacles {
+            solver.add_obstacle(&obstacle.in_cells(geometry.cast()));
+        }
+
+        let mut velocity_field = VectorField2D::new(width, height, [T::ZERO; 2]).with_geometry(geometry);
+        for (y, row) in velocity_field.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = parent.velocity_field.sample(geometry.position(T::from_usize(x), T::from_usize(y)));
+            }
+        }
+        solver.set_boundaries(&mut velocity_field);
+
+        self.levels.push(GridLevel { solver, velocity_field, offset, ratio });
+        self.levels.last_mut().expect("just pushed")
+    }
+
+    /// Adds an obstacle, in meters, to every level. Levels need square cells.
+    pub fn add_obstacle(&mut self, sdf: Sdf) {
+        for level in &mut self.levels {
+            let geometry = level.geometry();
+            level.solver.add_obstacle(&sdf.in_cells(geometry.cast()));
+            level.solver.set_boundaries(&mut level.velocity_field);
+        }
+        self.obstacles.push(sdf);
+    }
+
+    pub fn coarsest(&self) -> &GridLevel<T> {
+        &self.levels[0]
+    }
+
+    pub fn finest(&self) -> &GridLevel<T> {
+        self.levels.last().expect("a nested grid always has a coarsest level")
+    }
+
+    /// Finest level whose inner cells contain `position`, with the nearest cell on it.
+    pub fn level_at(&self, position: [T; 2]) -> (&GridLevel<T>, usize, usize) {
+        self.levels
+            .iter()
+            .rev()
+            .find_map(|level| level.cell(position).map(|(x, y)| (level, x, y)))
+            .unwrap_or_else(|| {
+                let level = self.coarsest();
+                let [x, y] = level.geometry().index(position);
+                let clamp = |value: T, size: usize| (value.round().max(T::ZERO).to_isize() as usize).min(size - 1);
+                (level, clamp(x, level.solver.width), clamp(y, level.solver.height))
+            })
+    }
+
+    /// Velocity at `position`, interpolated on the finest level that covers it.
+    pub fn sample(&self, position: [T; 2]) -> [T; 2] {
+        let (level, ..) = self.level_at(position);
+        level.velocity_field.sample(position)
+    }
+
+    pub fn step(&mut self, delta_time: T) {
+        for i in 0..self.levels.len() {
+            let (parents, levels) = self.levels.split_at_mut(i);
+            let level = &mut levels[0];
+            if let Some(parent) = parents.last() {
+                fill_edges(level, &parent.velocity_field);
+            }
+            level.solver.step(&mut level.velocity_field, delta_time);
+        }
+
+        for i in (1..self.levels.len()).rev() {
+            let (parents, levels) = self.levels.split_at_mut(i);
+            let parent = &mut parents[i - 1];
+            let covered = restrict(&levels[0], parent);
+            parent.solver.set_boundaries(&mut parent.velocity_field);
+            parent.solver.project_around(&mut parent.velocity_field, &covered);
+        }
+    }
+}
+
+/// Interpolates the edge cells of `level` from its parent's velocity, then evens out the
+/// normal velocity along the edges so that no net flow enters the patch, which its
+/// zero-gradient pressure edges could not otherwise balance.
+fn fill_edges<T: Real>(level: &mut GridLevel<T>, parent: &VectorField2D<T>) {
+    let geometry = level.geometry();
+    let (width, height) = (level.solver.width, level.solver.height);
+    let field = &mut level.velocity_field.field;
+    let sample = |x: usize, y: usize| parent.sample(geometry.position(T::from_usize(x), T::from_usize(y)));
+
+    for y in 0..height {
+        field[y][0] = sample(0, y);
+        field[y][width - 1] = sample(width - 1, y);
+    }
+    for x in 0..width {
+        field[0][x] = sample(x, 0);
+        field[height - 1][x] = sample(x, height - 1);
+    }
+
+    let [dx, dy] = geometry.spacing;
+    let mut outflow = T::ZERO;
+    for y in 1..height - 1 {
+        outflow += (field[y][width - 1][0] - field[y][0][0]) * dy;
+    }
+    for x in 1..width - 1 {
+        outflow += (field[height - 1][x][1] - field[0][x][1]) * dx;
+    }
+    let perimeter = T::from_f32(2.0) * (T::from_usize(height - 2) * dy + T::from_usize(width - 2) * dx);
+    let correction = outflow / perimeter;
+    for y in 1..height - 1 {
+        field[y][0][0] += correction;
+        field[y][width - 1][0] -= correction;
+    }
+    for x in 1..width - 1 {
+        field[0][x][1] += correction;
+        field[height - 1][x][1] -= correction;
+    }
+
+    level.solver.set_boundaries(&mut level.velocity_field);
+}
+
+/// Replaces each parent cell covered by `level` with the average of the fine cells inside it,
+/// and returns which parent cells were covered.
+fn restrict<T: Real>(level: &GridLevel<T>, parent: &mut GridLevel<T>) -> Grid2<bool> {
+    let ratio = level.ratio;
+    let (width, height) = ((level.solver.width - 2) / ratio, (level.solver.height - 2) / ratio);
+    let scale = T::ONE / T::from_usize(ratio * ratio);
+    let mut covered = Grid2::new(parent.solver.width, parent.solver.height, false);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [T::ZERO; 2];
+            for row in level.velocity_field.field.rows().skip(1 + y * ratio).take(ratio) {
+                for value in &row[1 + x * ratio..1 + (x + 1) * ratio] {
+                    sum[0] += value[0];
+                    sum[1] += value[1];
+                }
+            }
+            let (x, y) = (level.offset[0] + x, level.offset[1] + y);
+            parent.velocity_field.field[y][x] = [sum[0] * scale, sum[1] * scale];
+            covered[y][x] = true;
+        }
+    }
+    covered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 24 x 16 grid of half-meter cells, with a patch refining 8 x 6 of its cells by 3.
+    fn nested(velocity: impl Fn([f64; 2]) -> [f64; 2]) -> NestedGrid<f64> {
+        let geometry = GridGeometry::uniform(0.5);
+        let field = Grid2::from_fn(24, 16, |x, y| velocity(geometry.position(x as f64, y as f64)));
+        let mut nested = NestedGrid::new(FluidSolver::new(24, 16), VectorField2D { geometry, field });
+        nested.refine([6, 4], [8, 6], 3);
+        nested
+    }
+
+    #[test]
+    fn filled_edges_let_no_net_flow_into_the_patch() {
+        // A source spreading from the middle, which would fill the patch if left as it is.
+        let mut nested = nested(|[x, y]| [0.3 * (x - 6.0), 0.2 * (y - 4.0)]);
+        let parent = nested.levels[0].velocity_field.clone();
+        let level = &mut nested.levels[1];
+        fill_edges(level, &parent);
+
+        let [dx, dy] = level.geometry().spacing;
+        let (width, height) = (level.solver.width, level.solver.height);
+        let field = &level.velocity_field.field;
+        let outflow: f64 = (1..height - 1).map(|y| (field[y][width - 1][0] - field[y][0][0]) * dy).sum::<f64>()
+            + (1..width - 1).map(|x| (field[height - 1][x][1] - field[0][x][1]) * dx).sum::<f64>();
+        assert!(outflow.abs() < 1e-12, "net outflow {outflow}");
+    }
+
+    #[test]
+    fn restriction_keeps_the_momentum_of_the_patch() {
+        let mut nested = nested(|_| [0.0, 0.0]);
+        let fine_geometry = nested.levels[1].geometry();
+        for (y, row) in nested.levels[1].velocity_field.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = [(0.3 * x as f64).sin(), (0.2 * (x + y) as f64).cos()];
+            }
+        }
+        let fine_area = fine_geometry.cell_area();
+        let (width, height) = (nested.levels[1].solver.width, nested.levels[1].solver.height);
+        let mut fine_momentum = [0.0; 2];
+        for row in nested.levels[1].velocity_field.field.rows().skip(1).take(height - 2) {
+            for value in &row[1..width - 1] {
+                fine_momentum = [fine_momentum[0] + value[0] * fine_area, fine_momentum[1] + value[1] * fine_area];
+            }
+        }
+
+        let (parents, levels) = nested.levels.split_at_mut(1);
+        let covered = restrict(&levels[0], &mut parents[0]);
+        let coarse_area = parents[0].geometry().cell_area();
+        let mut coarse_momentum = [0.0; 2];
+        for (value, _) in parents[0].velocity_field.field.cells().zip(covered.cells()).filter(|(_, &covered)| covered) {
+            coarse_momentum = [coarse_momentum[0] + value[0] * coarse_area, coarse_momentum[1] + value[1] * coarse_area];
+        }
+
+        assert_eq!(covered.cells().filter(|&&covered| covered).count(), 8 * 6);
+        for c in 0..2 {
+            assert!((coarse_momentum[c] - fine_momentum[c]).abs() < 1e-12, "{coarse_momentum:?} against {fine_momentum:?}");
+        }
+    }
+}
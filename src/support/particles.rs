@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::field::VectorField2D;
+use super::grid::{Grid2, GridGeometry};
+use super::real::Real;
+
+/// Where new tracers appear, in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TracerEmitter<T = f32> {
+    /// Uniformly inside the rectangle `[x0, x1) x [y0, y1)`.
+    Region { x0: T, y0: T, x1: T, y1: T },
+    /// Uniformly inside a disc.
+    Point { x: T, y: T, radius: T },
+}
+
+impl<T: Real> TracerEmitter<T> {
+    fn sample(&self, rng: &mut StdRng) -> [T; 2] {
+        let mut uniform = |low: T, high: T| low + (high - low) * T::from_f64(rng.gen::<f64>());
+        match *self {
+            TracerEmitter::Region { x0, y0, x1, y1 } => [uniform(x0, x1), uniform(y0, y1)],
+            TracerEmitter::Point { x, y, radius } => {
+                let (sin, cos) = uniform(T::ZERO, T::from_f64(std::f64::consts::TAU)).sin_cos();
+                let distance = radius * uniform(T::ZERO, T::ONE).sqrt();
+                [x + distance * cos, y + distance * sin]
+            }
+        }
+    }
+}
+
+/// Time integration used to move tracers through the velocity field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    Euler,
+    Midpoint,
+    RungeKutta4,
+}
+
+/// A massless particle that follows the flow exactly.
+#[derive(Debug, Clone)]
+pub struct Tracer<T = f32> {
+    pub position: [T; 2],
+    pub age: T,
+    pub lifetime: T,
+    /// Most recent positions first.
+    pub trail: VecDeque<[T; 2]>,
+}
+
+/// Passive Lagrangian tracers advected by a `VectorField2D`, with positions in meters on the
+/// `width` x `height` grid placed by `geometry`. Tracers that grow older than their lifetime
+/// or leave the grid are respawned at one of the emitters.
+#[derive(Debug, Clone)]
+pub struct TracerSystem<T: Real = f32> {
+    pub geometry: GridGeometry<T>,
+    pub width: usize,
+    pub height: usize,
+    pub tracers: Vec<Tracer<T>>,
+    pub emitters: Vec<TracerEmitter<T>>,
+    pub integrator: Integrator,
+    pub lifetime: T,
+    pub trail_length: usize,
+    rng: StdRng,
+}
+
+impl<T: Real> TracerSystem<T> {
+    /// `count` tracers seeded over the inner cells of the grid of `velocity_field`.
+    pub fn new(velocity_field: &VectorField2D<T>, count: usize) -> Self {
+        let (geometry, width, height) = (velocity_field.geometry, velocity_field.width(), velocity_field.height());
+        let [x0, y0] = geometry.position(T::ONE, T::ONE);
+        let [x1, y1] = geometry.position(T::from_usize(width) - T::from_f32(2.0), T::from_usize(height) - T::from_f32(2.0));
+        Self::with_emitters(velocity_field, count, vec![TracerEmitter::Region { x0, y0, x1, y1 }])
+    }
+
+    pub fn with_emitters(velocity_field: &VectorField2D<T>, count: usize, emitters: Vec<TracerEmitter<T>>) -> Self {
+        let mut system = Self {
+            geometry: velocity_field.geometry,
+            width: velocity_field.width(),
+            height: velocity_field.height(),
+            tracers: Vec::with_capacity(count),
+            emitters,
+            integrator: Integrator::RungeKutta4,
+            lifetime: T::from_f32(40.0),
+            trail_length: 12,
+            rng: StdRng::seed_from_u64(0),
+        };
+        for _ in 0..count {
+            let mut tracer = system.spawn();
+            // Spread initial ages so tracers do not all respawn in the same frame.
+            tracer.age = tracer.lifetime * T::from_f64(system.rng.gen::<f64>());
+            system.tracers.push(tracer);
+        }
+        system
+    }
+
+    fn spawn(&mut self) -> Tracer<T> {
+        let emitter = self.emitters[self.rng.gen_range(0..self.emitters.len())];
+        let lifetime = self.lifetime * T::from_f64(self.rng.gen_range(0.75..1.25));
+        Tracer {
+            position: emitter.sample(&mut self.rng),
+            age: T::ZERO,
+            lifetime,
+            trail: VecDeque::with_capacity(self.trail_length),
+        }
+    }
+
+    fn is_inside(&self, position: [T; 2]) -> bool {
+        let [x, y] = self.geometry.index(position);
+        x >= T::ZERO && y >= T::ZERO && x <= T::from_usize(self.width - 1) && y <= T::from_usize(self.height - 1)
+    }
+
+    pub fn update(&mut self, velocity_field: &VectorField2D<T>, delta_time: T) {
+        for i in 0..self.tracers.len() {
+            let tracer = &mut self.tracers[i];
+            let position = integrate(velocity_field, tracer.position, delta_time, self.integrator);
+
+            if tracer.trail.len() == self.trail_length {
+                tracer.trail.pop_back();
+            }
+            tracer.trail.push_front(tracer.position);
+            tracer.position = position;
+            tracer.age += delta_time;
+
+            if tracer.age > tracer.lifetime || !self.is_inside(position) {
+                self.tracers[i] = self.spawn();
+            }
+        }
+    }
+}
+
+/// Moves a point, given in meters, through `velocity_field` over `delta_time`.
+pub fn integrate<T: Real>(velocity_field: &VectorField2D<T>, position: [T; 2], delta_time: T, integrator: Integrator) -> [T; 2] {
+    let velocity = |p: [T; 2]| velocity_field.sample(p);
+    let offset = |p: [T; 2], v: [T; 2], scale: T| [p[0] + v[0] * scale, p[1] + v[1] * scale];
+    let (half, two, six) = (T::from_f32(0.5), T::from_f32(2.0), T::from_f32(6.0));
+
+    match integrator {
+        Integrator::Euler => offset(position, velocity(position), delta_time),
+        Integrator::Midpoint => {
+            let midpoint = offset(position, velocity(position), delta_time * half);
+            offset(position, velocity(midpoint), delta_time)
+        }
+        Integrator::RungeKutta4 => {
+            let k1 = velocity(position);
+            let k2 = velocity(offset(position, k1, delta_time * half));
+            let k3 = velocity(offset(position, k2, delta_time * half));
+            let k4 = velocity(offset(position, k3, delta_time));
+            let average = [
+                (k1[0] + two * k2[0] + two * k3[0] + k4[0]) / six,
+                (k1[1] + two * k2[1] + two * k3[1] + k4[1]) / six,
+            ];
+            offset(position, average, delta_time)
+        }
+    }
+}
+
+/// A particle with its own inertia that lags behind the flow.
+#[derive(Debug, Clone, Copy)]
+pub struct InertialParticle<T = f32> {
+    pub position: [T; 2],
+    pub velocity: [T; 2],
+    pub radius: T,
+    pub density: T,
+}
+
+impl<T: Real> InertialParticle<T> {
+    /// Stokes response time `rho_p d^2 / (18 mu)`: how long the particle takes to catch up with the flow.
+    pub fn response_time(&self, fluid_viscosity: T) -> T {
+        let diameter = T::from_f32(2.0) * self.radius;
+        self.density * diameter * diameter / (T::from_f32(18.0) * fluid_viscosity)
+    }
+}
+
+/// Heavy particles such as sediment or dust, pulled by Stokes drag towards the local fluid
+/// velocity and by gravity (reduced by buoyancy), bouncing off the domain edges and solid cells.
+/// Positions are in meters, so the particles stay put when the grid under them is resampled.
+#[derive(Debug, Clone)]
+pub struct InertialParticleSystem<T: Real = f32> {
+    pub particles: Vec<InertialParticle<T>>,
+    pub gravity: [T; 2],
+    pub fluid_density: T,
+    /// Dynamic viscosity of the fluid used for Stokes drag.
+    pub fluid_viscosity: T,
+    /// Fraction of the normal velocity kept after hitting a wall.
+    pub restitution: T,
+    /// Fraction of the tangential velocity kept after hitting a wall.
+    pub wall_friction: T,
+}
+
+impl<T: Real> InertialParticleSystem<T> {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            gravity: [T::ZERO, T::ONE],
+            fluid_density: T::ONE,
+            fluid_viscosity: T::from_f32(0.01),
+            restitution: T::from_f32(0.3),
+            wall_friction: T::from_f32(0.8),
+        }
+    }
+
+    pub fn add(&mut self, position: [T; 2], radius: T, density: T) {
+        self.particles.push(InertialParticle { position, velocity: [T::ZERO; 2], radius, density });
+    }
+
+    /// Advances every particle. Drag is integrated implicitly, so particles much lighter
+    /// than `delta_time` allows simply take the fluid velocity instead of oscillating.
+    pub fn update(&mut self, velocity_field: &VectorField2D<T>, solid: Option<&Grid2<bool>>, delta_time: T) {
+        for i in 0..self.particles.len() {
+            let particle = self.particles[i];
+            let fluid_velocity = velocity_field.sample(particle.position);
+            let response_time = particle.response_time(self.fluid_viscosity);
+            let reduced_gravity = T::ONE - self.fluid_density / particle.density;
+
+            let mut velocity = [T::ZERO; 2];
+            for c in 0..2 {
+                let acceleration = fluid_velocity[c] / response_time + self.gravity[c] * reduced_gravity;
+                velocity[c] = (particle.velocity[c] + delta_time * acceleration) / (T::ONE + delta_time / response_time);
+            }
+
+            let mut position = [
+                particle.position[0] + velocity[0] * delta_time,
+                particle.position[1] + velocity[1] * delta_time,
+            ];
+            self.collide(velocity_field, &mut position, &mut velocity, particle.position, solid);
+
+            self.particles[i].position = position;
+            self.particles[i].velocity = velocity;
+        }
+    }
+
+    fn collide(&self, velocity_field: &VectorField2D<T>, position: &mut [T; 2], velocity: &mut [T; 2], previous: [T; 2], solid: Option<&Grid2<bool>>) {
+        let geometry = velocity_field.geometry;
+        let low = geometry.origin;
+        let high = geometry.position(T::from_usize(velocity_field.width() - 1), T::from_usize(velocity_field.height() - 1));
+        for c in 0..2 {
+            let tangent = 1 - c;
+            if position[c] < low[c] || position[c] > high[c] {
+                position[c] = position[c].clamp(low[c], high[c]);
+                velocity[c] = -velocity[c] * self.restitution;
+                velocity[tangent] *= self.wall_friction;
+            }
+        }
+
+        let Some(solid) = solid else {
+            return;
+        };
+        let is_solid = |p: [T; 2]| {
+            let [x, y] = geometry.index(p);
+            solid[y.round().to_isize() as usize][x.round().to_isize() as usize]
+        };
+        if !is_solid(*position) {
+            return;
+        }
+
+        // Bounce off whichever face of the solid cell was crossed, one axis at a time.
+        for c in 0..2 {
+            let mut moved_along_axis = previous;
+            moved_along_axis[c] = position[c];
+            if is_solid(moved_along_axis) {
+                position[c] = previous[c];
+                velocity[c] = -velocity[c] * self.restitution;
+                velocity[1 - c] *= self.wall_friction;
+            }
+        }
+        if is_solid(*position) {
+            *position = previous;
+            *velocity = [T::ZERO; 2];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Solid-body rotation at one radian per second around the middle of a 16 m square of
+    /// half-meter cells. The field is linear, so sampling it is exact.
+    fn rotation() -> VectorField2D<f64> {
+        let geometry = GridGeometry::uniform(0.5);
+        let field = Grid2::from_fn(33, 33, |x, y| {
+            let [px, py] = geometry.position(x as f64, y as f64);
+            [-(py - 8.0), px - 8.0]
+        });
+        VectorField2D { geometry, field }
+    }
+
+    #[test]
+    fn runge_kutta_closes_the_orbit_that_euler_spirals_off() {
+        let velocity_field = rotation();
+        let steps = 100;
+        let delta_time = std::f64::consts::TAU / steps as f64;
+        let orbit = |integrator| (0..steps).fold([12.0, 8.0], |position, _| integrate(&velocity_field, position, delta_time, integrator));
+
+        let [x, y] = orbit(Integrator::RungeKutta4);
+        assert!((x - 12.0).abs() < 1e-4 && (y - 8.0).abs() < 1e-4, "ended at ({x}, {y})");
+        let [x, y] = orbit(Integrator::Euler);
+        assert!(((x - 8.0).powi(2) + (y - 8.0).powi(2)).sqrt() > 4.5, "Euler should spiral outwards");
+    }
+
+    #[test]
+    fn tracers_spawn_at_their_emitter_and_stay_on_the_grid() {
+        let velocity_field = rotation();
+        let emitter = TracerEmitter::Point { x: 13.0, y: 8.0, radius: 2.0 };
+        let mut system = TracerSystem::with_emitters(&velocity_field, 200, vec![emitter]);
+        for tracer in &system.tracers {
+            let [x, y] = tracer.position;
+            assert!((x - 13.0).powi(2) + (y - 8.0).powi(2) <= 4.0);
+        }
+
+        for _ in 0..50 {
+            system.update(&velocity_field, 0.2);
+        }
+        for tracer in &system.tracers {
+            assert!(system.is_inside(tracer.position), "{:?} left the grid", tracer.position);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use super::field::VectorField2D;
+
+/// Records one velocity component at a fixed point every step, to measure oscillations.
+/// Only the most recent `capacity` samples are kept.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    /// Where the velocity is sampled, in meters.
+    pub position: [f32; 2],
+    /// Velocity component sampled: 0 for x, 1 for y.
+    pub component: usize,
+    pub time_step: f32,
+    pub capacity: usize,
+    pub samples: VecDeque<f32>,
+}
+
+impl Probe {
+    pub fn new(position: [f32; 2], component: usize, time_step: f32, capacity: usize) -> Self {
+        Self {
+            position,
+            component,
+            time_step,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record(&mut self, velocity_field: &VectorField2D) {
+        let velocity = velocity_field.sample(self.position);
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(velocity[self.component]);
+    }
+
+    /// The most recent `window` samples, oldest first.
+    fn recent(&self, window: usize) -> Vec<f32> {
+        let start = self.samples.len().saturating_sub(window);
+        self.samples.range(start..).copied().collect()
+    }
+
+    /// Dominant frequency over the most recent `window` samples, from the average spacing of
+    /// upward mean crossings (interpolated between samples). `None` until at least two full
+    /// periods have been recorded.
+    pub fn frequency(&self, window: usize) -> Option<f32> {
+        let samples = self.recent(window);
+        if samples.len() < 3 {
+            return None;
+        }
+
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let amplitude = samples.iter().map(|s| (s - mean).abs()).fold(0.0, f32::max);
+        if amplitude < 1e-4 {
+            return None;
+        }
+
+        let crossings: Vec<f32> = samples
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] - mean < 0.0 && pair[1] - mean >= 0.0)
+            .map(|(i, pair)| i as f32 + (mean - pair[0]) / (pair[1] - pair[0]))
+            .collect();
+        if crossings.len() < 3 {
+            return None;
+        }
+
+        let period = (crossings[crossings.len() - 1] - crossings[0]) / (crossings.len() - 1) as f32 * self.time_step;
+        Some(1.0 / period)
+    }
+
+    /// Peak-to-peak amplitude over the most recent `window` samples.
+    pub fn amplitude(&self, window: usize) -> f32 {
+        let samples = self.recent(window);
+        let max = samples.iter().cloned().fold(f32::MIN, f32::max);
+        let min = samples.iter().cloned().fold(f32::MAX, f32::min);
+        if samples.is_empty() { 0.0 } else { max - min }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_samples() {
+        // A 0.5 Hz oscillation sampled every 0.1 s.
+        let mut probe = Probe::new([0.5, 0.5], 0, 0.1, 500);
+        for step in 0..2000 {
+            let value = (std::f32::consts::PI * step as f32 * 0.1).sin();
+            probe.record(&VectorField2D::new(2, 2, [value, 0.0]));
+        }
+        assert_eq!(probe.samples.len(), 500);
+
+        let frequency = probe.frequency(2000).expect("the samples span many periods");
+        assert!((frequency - 0.5).abs() < 0.01, "frequency {frequency}");
+    }
+}
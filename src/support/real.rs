@@ -0,0 +1,131 @@
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Floating point type of the fields and solvers: `f32` for real-time scenes, `f64` for
+/// validation runs long enough for single precision round-off to show. Grid geometry and
+/// solver settings such as viscosities and edge velocities share the precision of the
+/// fields they act on, so an `f64` run never goes through a rounded `f32` value.
+pub trait Real:
+    Copy
+    + Debug
+    + Display
+    + Default
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+    + Send
+    + Sync
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_f32(value: f32) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f32(self) -> f32;
+    fn to_f64(self) -> f64;
+
+    fn from_usize(value: usize) -> Self {
+        Self::from_f64(value as f64)
+    }
+
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn powi(self, exponent: i32) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    /// Integer part, as used to find the cell a position falls in.
+    fn to_isize(self) -> isize;
+}
+
+macro_rules! impl_real {
+    ($type:ty) => {
+        impl Real for $type {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            fn from_f32(value: f32) -> Self {
+                value as Self
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value as Self
+            }
+
+            fn to_f32(self) -> f32 {
+                self as f32
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn floor(self) -> Self {
+                <$type>::floor(self)
+            }
+
+            fn round(self) -> Self {
+                <$type>::round(self)
+            }
+
+            fn abs(self) -> Self {
+                <$type>::abs(self)
+            }
+
+            fn sqrt(self) -> Self {
+                <$type>::sqrt(self)
+            }
+
+            fn exp(self) -> Self {
+                <$type>::exp(self)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                <$type>::sin_cos(self)
+            }
+
+            fn powi(self, exponent: i32) -> Self {
+                <$type>::powi(self, exponent)
+            }
+
+            fn powf(self, exponent: Self) -> Self {
+                <$type>::powf(self, exponent)
+            }
+
+            fn max(self, other: Self) -> Self {
+                <$type>::max(self, other)
+            }
+
+            fn min(self, other: Self) -> Self {
+                <$type>::min(self, other)
+            }
+
+            fn clamp(self, min: Self, max: Self) -> Self {
+                <$type>::clamp(self, min, max)
+            }
+
+            fn to_isize(self) -> isize {
+                self as isize
+            }
+        }
+    };
+}
+
+impl_real!(f32);
+impl_real!(f64);
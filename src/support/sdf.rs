@@ -0,0 +1,212 @@
+use super::field::ColorField2D;
+use super::grid::GridGeometry;
+
+/// Subcells per side used to estimate the coverage of cells crossed by a boundary.
+const COVERAGE_SAMPLES: usize = 4;
+
+/// Obstacle shape described by a signed distance function, in cell coordinates: negative
+/// inside, positive outside. Shapes combine with constructive solid geometry, e.g.
+/// `Sdf::circle([40.0, 30.0], 10.0).difference(Sdf::circle([44.0, 30.0], 6.0))` for a crescent.
+///
+/// The combinations give a bound on the distance rather than the exact distance away from
+/// the surface, which is all the rasterization needs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sdf {
+    Circle { center: [f32; 2], radius: f32 },
+    /// Rectangle rotated by `angle` radians about its center.
+    Box { center: [f32; 2], half_size: [f32; 2], angle: f32 },
+    /// Segment from `a` to `b` thickened by `radius`.
+    Capsule { a: [f32; 2], b: [f32; 2], radius: f32 },
+    /// Closed polygon, with vertices in either winding order.
+    Polygon(Vec<[f32; 2]>),
+    /// Several closed loops filled by the even-odd rule, so loops inside others cut holes,
+    /// as produced by slicing a mesh.
+    Region(Vec<Vec<[f32; 2]>>),
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    /// The first shape with the second carved out of it.
+    Difference(Box<Sdf>, Box<Sdf>),
+    /// A shape described in another frame, where the point `p` of this one lies at
+    /// `offset + p * scale`; used to rasterize one obstacle on grids of different cell sizes.
+    Mapped { shape: Box<Sdf>, offset: [f32; 2], scale: f32 },
+}
+
+impl Sdf {
+    pub fn circle(center: [f32; 2], radius: f32) -> Self {
+        Sdf::Circle { center, radius }
+    }
+
+    pub fn rectangle(center: [f32; 2], half_size: [f32; 2], angle: f32) -> Self {
+        Sdf::Box { center, half_size, angle }
+    }
+
+    pub fn capsule(a: [f32; 2], b: [f32; 2], radius: f32) -> Self {
+        Sdf::Capsule { a, b, radius }
+    }
+
+    pub fn polygon(vertices: Vec<[f32; 2]>) -> Self {
+        Sdf::Polygon(vertices)
+    }
+
+    pub fn region(loops: Vec<Vec<[f32; 2]>>) -> Self {
+        Sdf::Region(loops)
+    }
+
+    pub fn union(self, other: Sdf) -> Self {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersection(self, other: Sdf) -> Self {
+        Sdf::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn difference(self, other: Sdf) -> Self {
+        Sdf::Difference(Box::new(self), Box::new(other))
+    }
+
+    pub fn mapped(self, offset: [f32; 2], scale: f32) -> Self {
+        Sdf::Mapped { shape: Box::new(self), offset, scale }
+    }
+
+    /// This shape, given in meters, in the cell coordinates of a grid placed by `geometry`.
+    /// The grid needs square cells.
+    pub fn in_cells(&self, geometry: GridGeometry) -> Self {
+        debug_assert_eq!(geometry.spacing[0], geometry.spacing[1], "obstacles need square cells");
+        self.clone().mapped(geometry.origin, geometry.spacing[0])
+    }
+
+    pub fn distance(&self, point: [f32; 2]) -> f32 {
+        match self {
+            Sdf::Circle { center, radius } => length(sub(point, *center)) - radius,
+            Sdf::Box { center, half_size, angle } => {
+                let (sin, cos) = angle.sin_cos();
+                let [dx, dy] = sub(point, *center);
+                let local = [(dx * cos + dy * sin).abs() - half_size[0], (-dx * sin + dy * cos).abs() - half_size[1]];
+                length([local[0].max(0.0), local[1].max(0.0)]) + local[0].max(local[1]).min(0.0)
+            }
+            Sdf::Capsule { a, b, radius } => {
+                let (pa, ba) = (sub(point, *a), sub(*b, *a));
+                let t = (dot(pa, ba) / dot(ba, ba).max(f32::EPSILON)).clamp(0.0, 1.0);
+                length([pa[0] - ba[0] * t, pa[1] - ba[1] * t]) - radius
+            }
+            Sdf::Polygon(vertices) => polygon_distance(std::slice::from_ref(vertices), point),
+            Sdf::Region(loops) => polygon_distance(loops, point),
+            Sdf::Union(a, b) => a.distance(point).min(b.distance(point)),
+            Sdf::Intersection(a, b) => a.distance(point).max(b.distance(point)),
+            Sdf::Difference(a, b) => a.distance(point).max(-b.distance(point)),
+            Sdf::Mapped { shape, offset, scale } => shape.distance([offset[0] + point[0] * scale, offset[1] + point[1] * scale]) / scale,
+        }
+    }
+
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        self.distance(point) < 0.0
+    }
+
+    /// Fraction of the unit cell centered on `(x, y)` that lies inside the shape. Cells the
+    /// boundary crosses are split into subcells, each covered according to the distance at
+    /// its center, so curved and slanted walls give smoothly varying fractions.
+    pub fn coverage(&self, x: f32, y: f32) -> f32 {
+        let distance = self.distance([x, y]);
+        // Half the cell diagonal: farther than this, the boundary cannot cross the cell.
+        if distance >= std::f32::consts::FRAC_1_SQRT_2 {
+            return 0.0;
+        }
+        if distance <= -std::f32::consts::FRAC_1_SQRT_2 {
+            return 1.0;
+        }
+
+        let step = 1.0 / COVERAGE_SAMPLES as f32;
+        let mut covered = 0.0;
+        for j in 0..COVERAGE_SAMPLES {
+            for i in 0..COVERAGE_SAMPLES {
+                let sample = [x - 0.5 + (i as f32 + 0.5) * step, y - 0.5 + (j as f32 + 0.5) * step];
+                covered += (0.5 - self.distance(sample) / step).clamp(0.0, 1.0);
+            }
+        }
+        covered / (COVERAGE_SAMPLES * COVERAGE_SAMPLES) as f32
+    }
+
+    /// Coverage of every cell of a `width` x `height` grid.
+    pub fn rasterize(&self, width: usize, height: usize) -> ColorField2D {
+        let mut coverage = ColorField2D::new(width, height, 0.0);
+        for (y, row) in coverage.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = self.coverage(x as f32, y as f32);
+            }
+        }
+        coverage
+    }
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn length(a: [f32; 2]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Exact distance to the nearest edge of any loop, negative inside by the even-odd rule.
+fn polygon_distance(loops: &[Vec<[f32; 2]>], point: [f32; 2]) -> f32 {
+    let mut distance_squared = f32::MAX;
+    let mut inside = false;
+    for vertices in loops.iter().filter(|vertices| !vertices.is_empty()) {
+        let mut j = vertices.len() - 1;
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[j]);
+            let (edge, offset) = (sub(b, a), sub(point, a));
+            let t = (dot(offset, edge) / dot(edge, edge).max(f32::EPSILON)).clamp(0.0, 1.0);
+            let nearest = [offset[0] - edge[0] * t, offset[1] - edge[1] * t];
+            distance_squared = distance_squared.min(dot(nearest, nearest));
+
+            if (a[1] > point[1]) != (b[1] > point[1]) && point[0] < edge[0] * (point[1] - a[1]) / edge[1] + a[0] {
+                inside = !inside;
+            }
+            j = i;
+        }
+    }
+    let distance = distance_squared.sqrt();
+    if inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_coverage_adds_up_to_its_area() {
+        // A 6 m circle on one-meter and on quarter-meter cells, in cells.
+        for spacing in [1.0, 0.25] {
+            let cells = (32.0 / spacing) as usize;
+            let circle = Sdf::circle([15.3, 16.7], 6.0).in_cells(GridGeometry::uniform(spacing));
+            let covered: f32 = circle.rasterize(cells, cells).field.cells().sum();
+            let area = std::f32::consts::PI * 36.0 / (spacing * spacing);
+            assert!((covered - area).abs() < 0.005 * area, "{covered} cells instead of {area} on {spacing} m cells");
+        }
+    }
+
+    #[test]
+    fn holes_and_carved_shapes_are_outside() {
+        let ring = Sdf::region(vec![
+            vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]],
+            vec![[3.0, 3.0], [7.0, 3.0], [7.0, 7.0], [3.0, 7.0]],
+        ]);
+        assert!(ring.contains([1.5, 5.0]));
+        assert!(!ring.contains([5.0, 5.0]));
+        assert!((ring.distance([5.0, 5.0]) - 2.0).abs() < 1e-6);
+
+        let crescent = Sdf::circle([0.0, 0.0], 4.0).difference(Sdf::circle([2.0, 0.0], 3.0));
+        assert!(crescent.contains([-3.0, 0.0]));
+        assert!(!crescent.contains([1.0, 0.0]));
+        assert_eq!(crescent.coverage(1.0, 0.0), 0.0);
+        assert_eq!(crescent.coverage(-3.0, 0.0), 1.0);
+    }
+}
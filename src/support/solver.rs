@@ -0,0 +1,435 @@
+use super::diagnostics::Diagnostics;
+use super::field::{ColorField2D, Field2D, VectorField2D};
+use super::grid::{Grid2, GridGeometry};
+use super::real::Real;
+use super::sdf::Sdf;
+use super::turbulence::Smagorinsky;
+use super::viscosity::ViscosityModel;
+
+/// Cells with less open area than this are treated as fully solid.
+pub const MIN_FLUID_FRACTION: f32 = 0.05;
+/// Most pressure solves spent bringing a resampled velocity back to its former divergence.
+const MAX_RESAMPLE_PROJECTIONS: usize = 20;
+
+/// What happens at one edge of the domain, with velocities in the precision `T` of the solver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeCondition<T = f32> {
+    /// Free-slip wall: no flow through it, tangential velocity unchanged.
+    Wall,
+    /// Fluid enters with a fixed velocity.
+    Inflow([T; 2]),
+    /// Fluid leaves freely: zero velocity gradient and zero pressure.
+    Outflow,
+    /// No-slip wall moving along itself with the given velocity, like the lid of a
+    /// driven cavity. `MovingWall([0.0, 0.0])` is a stationary no-slip wall.
+    MovingWall([T; 2]),
+    /// Edge cells keep whatever was written into them, such as velocities interpolated from
+    /// an enclosing coarser grid.
+    Fixed,
+}
+
+impl<T: Real> EdgeCondition<T> {
+    /// The same condition in another precision.
+    pub fn cast<U: Real>(&self) -> EdgeCondition<U> {
+        let cast = |velocity: [T; 2]| velocity.map(|c| U::from_f64(c.to_f64()));
+        match *self {
+            EdgeCondition::Wall => EdgeCondition::Wall,
+            EdgeCondition::Inflow(velocity) => EdgeCondition::Inflow(cast(velocity)),
+            EdgeCondition::Outflow => EdgeCondition::Outflow,
+            EdgeCondition::MovingWall(velocity) => EdgeCondition::MovingWall(cast(velocity)),
+            EdgeCondition::Fixed => EdgeCondition::Fixed,
+        }
+    }
+}
+
+/// Incompressible solver for a `VectorField2D`: self-advection, viscous diffusion
+/// and a pressure projection, with configurable domain edges and optional solid
+/// cells inside the domain. Works in the precision `T` of the velocity it steps, settings
+/// such as the viscosity and edge velocities included.
+#[derive(Debug, Clone)]
+pub struct FluidSolver<T: Real = f32> {
+    pub width: usize,
+    pub height: usize,
+    pub viscosity: ViscosityModel<T>,
+    /// Subgrid model for flows too turbulent for the grid; its eddy viscosity is added to
+    /// the fluid's own in the diffusion step.
+    pub turbulence: Option<Smagorinsky<T>>,
+    pub iterations: usize,
+    pub pressure: ColorField2D<T>,
+    pub divergence: ColorField2D<T>,
+    /// Per-cell viscosity used by the last diffusion step, eddy viscosity included.
+    pub viscosity_field: ColorField2D<T>,
+    /// Per-cell eddy viscosity from the turbulence model in the last diffusion step.
+    pub eddy_viscosity: ColorField2D<T>,
+    /// Obstacle cells, where the velocity is held at zero.
+    pub solid: Grid2<bool>,
+    /// Open fraction of each cell, below 1 where an obstacle partly covers it. The pressure
+    /// solve weights the faces between cells by it, so curved walls are not stair-stepped.
+    pub fluid_fraction: ColorField2D<T>,
+    /// Left, right, top and bottom edges.
+    pub edges: [EdgeCondition<T>; 4],
+}
+
+impl<T: Real> FluidSolver<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            viscosity: ViscosityModel::Newtonian(T::ZERO),
+            turbulence: None,
+            iterations: 40,
+            pressure: ColorField2D::new(width, height, T::ZERO),
+            divergence: ColorField2D::new(width, height, T::ZERO),
+            viscosity_field: ColorField2D::new(width, height, T::ZERO),
+            eddy_viscosity: ColorField2D::new(width, height, T::ZERO),
+            solid: Grid2::new(width, height, false),
+            fluid_fraction: ColorField2D::new(width, height, T::ONE),
+            edges: [EdgeCondition::Wall; 4],
+        }
+    }
+
+    /// The same solver, obstacles and pressure included, in another precision.
+    pub fn cast<U: Real>(&self) -> FluidSolver<U> {
+        FluidSolver {
+            width: self.width,
+            height: self.height,
+            viscosity: self.viscosity.cast(),
+            turbulence: self.turbulence.map(|turbulence| turbulence.cast()),
+            iterations: self.iterations,
+            pressure: self.pressure.cast(),
+            divergence: self.divergence.cast(),
+            viscosity_field: self.viscosity_field.cast(),
+            eddy_viscosity: self.eddy_viscosity.cast(),
+            solid: self.solid.clone(),
+            fluid_fraction: self.fluid_fraction.cast(),
+            edges: self.edges.map(|edge| edge.cast()),
+        }
+    }
+
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        self.solid[y][x]
+    }
+
+    /// Marks the cells in `[x0, x1) x [y0, y1)` as solid.
+    pub fn add_solid_rectangle(&mut self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        for row in self.solid.rows_mut().take(y1.min(self.height)).skip(y0) {
+            for cell in &mut row[x0.min(self.width)..x1.min(self.width)] {
+                *cell = true;
+            }
+        }
+    }
+
+    /// Marks the cells whose center lies within `radius` of `center`, both in meters on a
+    /// grid placed by `geometry`.
+    pub fn add_solid_circle(&mut self, geometry: &GridGeometry<T>, center: [T; 2], radius: T) {
+        for (cy, row) in self.solid.rows_mut().enumerate() {
+            for (cx, cell) in row.iter_mut().enumerate() {
+                let [x, y] = geometry.position(T::from_usize(cx), T::from_usize(cy));
+                if (x - center[0]).powi(2) + (y - center[1]).powi(2) < radius * radius {
+                    *cell = true;
+                }
+            }
+        }
+    }
+
+    /// Adds an obstacle covering each cell by the fraction of its area inside `sdf`. Cells
+    /// left with almost no open area become solid.
+    pub fn add_obstacle(&mut self, sdf: &Sdf) {
+        let coverage = sdf.rasterize(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let fraction = &mut self.fluid_fraction.field[y][x];
+                *fraction = fraction.min(T::from_f32(1.0 - coverage.field[y][x]));
+                if *fraction < T::from_f32(MIN_FLUID_FRACTION) {
+                    self.solid[y][x] = true;
+                }
+            }
+        }
+    }
+
+    /// Removes every obstacle, solid cells included.
+    pub fn clear_obstacles(&mut self) {
+        self.solid = Grid2::new(self.width, self.height, false);
+        self.fluid_fraction = ColorField2D::new(self.width, self.height, T::ONE);
+    }
+
+    /// Changes the resolution to `width` x `height` cells over the same domain, carrying the
+    /// obstacles, pressure and `velocity_field` over by conservative resampling. The velocity
+    /// is then projected, repeatedly if needed, to bring its divergence on the new grid back
+    /// down to what it was on the old one.
+    pub fn resample(&mut self, velocity_field: &mut VectorField2D<T>, width: usize, height: usize) {
+        let target = Diagnostics::measure(velocity_field, None, 0.0).l2_divergence;
+
+        let solid = Field2D {
+            geometry: velocity_field.geometry,
+            field: self.solid.map(|&solid| if solid { T::ONE } else { T::ZERO }),
+        };
+        let solid = solid.resample(width, height);
+        self.fluid_fraction = self.fluid_fraction.resample(width, height);
+        self.solid = Grid2::from_fn(width, height, |x, y| {
+            solid.field[y][x] >= T::from_f32(0.5) || self.fluid_fraction.field[y][x] < T::from_f32(MIN_FLUID_FRACTION)
+        });
+
+        self.pressure = self.pressure.resample(width, height);
+        self.divergence = ColorField2D::new(width, height, T::ZERO);
+        self.viscosity_field = ColorField2D::new(width, height, T::ZERO);
+        self.eddy_viscosity = ColorField2D::new(width, height, T::ZERO);
+        self.width = width;
+        self.height = height;
+
+        *velocity_field = velocity_field.resample(width, height);
+        self.set_boundaries(velocity_field);
+        for _ in 0..MAX_RESAMPLE_PROJECTIONS {
+            self.project(velocity_field);
+            if Diagnostics::measure(velocity_field, None, 0.0).l2_divergence <= target {
+                break;
+            }
+        }
+    }
+
+    pub fn step(&mut self, velocity_field: &mut VectorField2D<T>, delta_time: T) {
+        *velocity_field = velocity_field.advect(delta_time);
+        self.set_boundaries(velocity_field);
+        self.diffuse(velocity_field, delta_time);
+        self.project(velocity_field);
+    }
+
+    /// Implicit viscous diffusion of the full stress `div(2 mu S)`, with the strain rate
+    /// `S = (grad u + grad u^T) / 2`, solved with Gauss-Seidel iterations. The viscosity is
+    /// evaluated per cell from the current shear rate, plus the eddy viscosity of the
+    /// turbulence model if any, and averaged onto the faces between cells.
+    ///
+    /// Each component diffuses implicitly with `2 mu` along its own axis and `mu` across it;
+    /// the cross derivatives `d/dy (mu dv/dx)` and `d/dx (mu du/dy)` are taken from the latest
+    /// iterate. With uniform viscosity and a divergence-free flow they cancel the extra normal
+    /// stress and leave `mu laplacian(u)`, but where the viscosity varies they carry the
+    /// transposed gradient that a per-component Laplacian drops.
+    pub fn diffuse(&mut self, velocity_field: &mut VectorField2D<T>, delta_time: T) {
+        if self.viscosity == ViscosityModel::Newtonian(T::ZERO) && self.turbulence.is_none() {
+            return;
+        }
+
+        self.viscosity.evaluate(velocity_field, &mut self.viscosity_field);
+        if let Some(turbulence) = &self.turbulence {
+            turbulence.evaluate(velocity_field, &mut self.eddy_viscosity);
+            for (row, eddy_row) in self.viscosity_field.field.rows_mut().zip(self.eddy_viscosity.field.rows()) {
+                for (viscosity, eddy_viscosity) in row.iter_mut().zip(eddy_row) {
+                    *viscosity += *eddy_viscosity;
+                }
+            }
+        }
+        let viscosity = &self.viscosity_field.field;
+        let initial = velocity_field.field.clone();
+        let [dx, dy] = velocity_field.geometry.spacing;
+        let (horizontal, vertical) = (delta_time / (dx * dx), delta_time / (dy * dy));
+        let (half, two) = (T::from_f32(0.5), T::from_f32(2.0));
+        // Scale of the horizontal and vertical faces for each component.
+        let scales = [[two * horizontal, vertical], [horizontal, two * vertical]];
+
+        for _ in 0..self.iterations {
+            for y in 1..self.height - 1 {
+                for x in 1..self.width - 1 {
+                    if self.solid[y][x] {
+                        continue;
+                    }
+                    let field = &velocity_field.field;
+                    let face = |nx: usize, ny: usize| half * (viscosity[y][x] + viscosity[ny][nx]);
+                    let (left, right) = (face(x - 1, y), face(x + 1, y));
+                    let (up, down) = (face(x, y - 1), face(x, y + 1));
+
+                    let dvdx = |row: usize| viscosity[row][x] * (field[row][x + 1][1] - field[row][x - 1][1]) / (two * dx);
+                    let dudy = |column: usize| viscosity[y][column] * (field[y + 1][column][0] - field[y - 1][column][0]) / (two * dy);
+                    let cross = [
+                        delta_time * (dvdx(y + 1) - dvdx(y - 1)) / (two * dy),
+                        delta_time * (dudy(x + 1) - dudy(x - 1)) / (two * dx),
+                    ];
+
+                    let mut updated = [T::ZERO; 2];
+                    for (c, value) in updated.iter_mut().enumerate() {
+                        let [horizontal, vertical] = scales[c];
+                        let (left, right) = (left * horizontal, right * horizontal);
+                        let (up, down) = (up * vertical, down * vertical);
+                        let neighbours = left * field[y][x - 1][c] + right * field[y][x + 1][c] + up * field[y - 1][x][c] + down * field[y + 1][x][c];
+                        *value = (initial[y][x][c] + neighbours + cross[c]) / (T::ONE + left + right + up + down);
+                    }
+                    velocity_field.field[y][x] = updated;
+                }
+            }
+            self.set_boundaries(velocity_field);
+        }
+    }
+
+    /// Removes the divergent part of the velocity by solving a Poisson equation for pressure.
+    /// The pressure from the previous step is kept as the initial guess. Solid cells act as
+    /// walls: the pressure gradient into them is zero. Faces partly blocked by an obstacle
+    /// are weighted by their open fraction, both in the flux through them and in the solve.
+    /// Derivatives use the cell spacing of the velocity field's geometry.
+    pub fn project(&mut self, velocity_field: &mut VectorField2D<T>) {
+        self.project_where(velocity_field, |_, _| false);
+    }
+
+    /// Projection that leaves the velocity of the cells marked in `held` untouched. Held cells
+    /// are treated like moving walls: their flux still enters the divergence of the open cells
+    /// next to them, but they get no pressure of their own and no pressure gradient acts across
+    /// their faces. The open cells are made divergence free around the held ones, provided no
+    /// net flow leaves the held region.
+    pub fn project_around(&mut self, velocity_field: &mut VectorField2D<T>, held: &Grid2<bool>) {
+        self.project_where(velocity_field, |x, y| held[y][x]);
+    }
+
+    fn project_where(&mut self, velocity_field: &mut VectorField2D<T>, held: impl Fn(usize, usize) -> bool + Sync) {
+        let (width, height) = (self.width, self.height);
+        let fraction = &self.fluid_fraction.field;
+        let [dx, dy] = velocity_field.geometry.spacing;
+        let half = T::from_f32(0.5);
+        let solid = &self.solid;
+        // Open fraction of the face between two neighbouring cells; closed next to solid or held cells.
+        let face_fraction = |x: usize, y: usize, nx: usize, ny: usize| {
+            if solid[y][x] || solid[ny][nx] || held(nx, ny) {
+                T::ZERO
+            } else {
+                half * (fraction[y][x] + fraction[ny][nx])
+            }
+        };
+
+        let field = &velocity_field.field;
+        let flux = |nx: usize, ny: usize, c: usize| fraction[ny][nx] * field[ny][nx][c];
+        self.divergence.field.par_rows_mut(|y, row| {
+            if y == 0 || y == height - 1 {
+                return;
+            }
+            for (x, divergence) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                *divergence = half * ((flux(x + 1, y, 0) - flux(x - 1, y, 0)) / dx + (flux(x, y + 1, 1) - flux(x, y - 1, 1)) / dy);
+            }
+        });
+
+        for _ in 0..self.iterations {
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    if self.solid[y][x] || held(x, y) {
+                        continue;
+                    }
+                    let mut neighbours = T::ZERO;
+                    let mut weight = T::ZERO;
+                    for (nx, ny, spacing) in [(x - 1, y, dx), (x + 1, y, dx), (x, y - 1, dy), (x, y + 1, dy)] {
+                        let face = face_fraction(x, y, nx, ny) / (spacing * spacing);
+                        neighbours += face * self.pressure.field[ny][nx];
+                        weight += face;
+                    }
+                    if weight > T::ZERO {
+                        self.pressure.field[y][x] = (neighbours - self.divergence.field[y][x]) / weight;
+                    }
+                }
+            }
+            set_pressure_boundaries(&mut self.pressure, &self.edges);
+        }
+
+        let pressure = &self.pressure.field;
+        velocity_field.field.par_rows_mut(|y, row| {
+            if y == 0 || y == height - 1 {
+                return;
+            }
+            for (x, velocity) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                if solid[y][x] || held(x, y) {
+                    continue;
+                }
+                let gradient = |nx: usize, ny: usize| face_fraction(x, y, nx, ny) * (pressure[ny][nx] - pressure[y][x]);
+                velocity[0] -= half * (gradient(x + 1, y) - gradient(x - 1, y)) / dx;
+                velocity[1] -= half * (gradient(x, y + 1) - gradient(x, y - 1)) / dy;
+            }
+        });
+
+        self.set_boundaries(velocity_field);
+    }
+
+    /// Sets the edge cells of the velocity from the edge conditions, and zeroes solid cells.
+    pub fn set_boundaries(&self, velocity_field: &mut VectorField2D<T>) {
+        let (width, height) = (self.width, self.height);
+        let field = &mut velocity_field.field;
+
+        let edge_value = |condition: EdgeCondition<T>, inner: [T; 2], current: [T; 2], normal: usize| match condition {
+            EdgeCondition::Wall => {
+                let mut value = inner;
+                value[normal] = -value[normal];
+                value
+            }
+            EdgeCondition::Inflow(velocity) => velocity,
+            EdgeCondition::Outflow => inner,
+            // The wall lies halfway between the edge cell and the first inner cell.
+            EdgeCondition::MovingWall(velocity) => {
+                let [u, v] = velocity.map(|c| T::from_f32(2.0) * c);
+                let mut value = [u - inner[0], v - inner[1]];
+                value[normal] = -inner[normal];
+                value
+            }
+            EdgeCondition::Fixed => current,
+        };
+
+        for y in 1..height - 1 {
+            field[y][0] = edge_value(self.edges[0], field[y][1], field[y][0], 0);
+            field[y][width - 1] = edge_value(self.edges[1], field[y][width - 2], field[y][width - 1], 0);
+        }
+        for x in 1..width - 1 {
+            field[0][x] = edge_value(self.edges[2], field[1][x], field[0][x], 1);
+            field[height - 1][x] = edge_value(self.edges[3], field[height - 2][x], field[height - 1][x], 1);
+        }
+
+        if !self.edges.contains(&EdgeCondition::Fixed) {
+            set_corners(field, width, height);
+        }
+
+        for (row, solid) in field.rows_mut().zip(self.solid.rows()) {
+            for (velocity, &solid) in row.iter_mut().zip(solid) {
+                if solid {
+                    *velocity = [T::ZERO; 2];
+                }
+            }
+        }
+    }
+}
+
+/// Zero pressure at outflow edges, zero normal gradient everywhere else.
+fn set_pressure_boundaries<T: Real>(pressure: &mut ColorField2D<T>, edges: &[EdgeCondition<T>; 4]) {
+    let (width, height) = (pressure.width(), pressure.height());
+    set_scalar_boundaries(pressure);
+
+    let field = &mut pressure.field;
+    for y in 0..height {
+        if edges[0] == EdgeCondition::Outflow {
+            field[y][0] = T::ZERO;
+        }
+        if edges[1] == EdgeCondition::Outflow {
+            field[y][width - 1] = T::ZERO;
+        }
+    }
+    for x in 0..width {
+        if edges[2] == EdgeCondition::Outflow {
+            field[0][x] = T::ZERO;
+        }
+        if edges[3] == EdgeCondition::Outflow {
+            field[height - 1][x] = T::ZERO;
+        }
+    }
+}
+
+pub fn set_scalar_boundaries<T: Real>(scalar_field: &mut ColorField2D<T>) {
+    let (width, height) = (scalar_field.width(), scalar_field.height());
+    let field = &mut scalar_field.field;
+
+    for row in field.rows_mut() {
+        row[0] = row[1];
+        row[width - 1] = row[width - 2];
+    }
+    field.copy_row(1, 0);
+    field.copy_row(height - 2, height - 1);
+}
+
+fn set_corners<T: Real>(field: &mut Grid2<[T; 2]>, width: usize, height: usize) {
+    let half = T::from_f32(0.5);
+    let average = |a: [T; 2], b: [T; 2]| [(a[0] + b[0]) * half, (a[1] + b[1]) * half];
+
+    field[0][0] = average(field[0][1], field[1][0]);
+    field[0][width - 1] = average(field[0][width - 2], field[1][width - 1]);
+    field[height - 1][0] = average(field[height - 1][1], field[height - 2][0]);
+    field[height - 1][width - 1] = average(field[height - 1][width - 2], field[height - 2][width - 1]);
+}
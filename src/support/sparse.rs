@@ -0,0 +1,333 @@
+use std::fmt::Debug;
+
+use super::field::CellValue;
+use super::grid::{Grid2, GridGeometry, OutOfBounds};
+use super::real::Real;
+
+/// Cells per side of a tile.
+pub const TILE_SIZE: usize = 8;
+const TILE_CELLS: usize = TILE_SIZE * TILE_SIZE;
+
+/// Values a sparse field can hold: the cell values of the dense fields, which can also be
+/// compared with the background.
+pub trait TileValue: CellValue + PartialEq + Debug {
+    /// Largest difference between components.
+    fn difference(a: Self, b: Self) -> Self::Scalar;
+}
+
+impl<T: Real> TileValue for T {
+    fn difference(a: Self, b: Self) -> T {
+        (a - b).abs()
+    }
+}
+
+impl<T: Real, const N: usize> TileValue for [T; N] {
+    fn difference(a: Self, b: Self) -> T {
+        a.into_iter().zip(b).map(|(a, b)| (a - b).abs()).fold(T::ZERO, T::max)
+    }
+}
+
+type Tile<T> = Box<[T; TILE_CELLS]>;
+
+/// Grid split into `TILE_SIZE` x `TILE_SIZE` tiles that are only allocated once something is
+/// written to them; every cell of a missing tile reads as `background`. A smoke plume in a
+/// big room then costs memory and time in proportion to the plume, not the room. Like the
+/// dense `Field2D`, the grid is placed in the physical world by `geometry`, with `x` along
+/// rows and `y` down, and velocities are in meters per second.
+#[derive(Debug, Clone)]
+pub struct SparseField2D<V: TileValue> {
+    pub geometry: GridGeometry<V::Scalar>,
+    /// Value of every cell that has not been written to.
+    pub background: V,
+    width: usize,
+    height: usize,
+    tiles_x: usize,
+    tiles_y: usize,
+    tiles: Vec<Option<Tile<V>>>,
+}
+
+/// Sparse counterpart of `ColorField2D`.
+pub type SparseColorField2D<T = f32> = SparseField2D<T>;
+/// Sparse counterpart of `VectorField2D`.
+pub type SparseVectorField2D<T = f32> = SparseField2D<[T; 2]>;
+
+impl<V: TileValue> SparseField2D<V> {
+    pub fn new(width: usize, height: usize, background: V) -> Self {
+        let (tiles_x, tiles_y) = (width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE));
+        Self {
+            geometry: GridGeometry::UNIT,
+            background,
+            width,
+            height,
+            tiles_x,
+            tiles_y,
+            tiles: vec![None; tiles_x * tiles_y],
+        }
+    }
+
+    /// The same field placed in the physical world by `geometry`.
+    pub fn with_geometry(mut self, geometry: GridGeometry<V::Scalar>) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
+    /// Copies a dense grid, allocating only the tiles with a cell that differs from
+    /// `background`.
+    pub fn from_grid(grid: &Grid2<V>, background: V) -> Self {
+        let mut sparse = Self::new(grid.width(), grid.height(), background);
+        for (y, row) in grid.rows().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                sparse.write(x, y, value);
+            }
+        }
+        sparse
+    }
+
+    /// Dense copy, as stored by `Field2D`.
+    pub fn to_grid(&self) -> Grid2<V> {
+        Grid2::from_fn(self.width, self.height, |x, y| self.value(x, y))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn tile_index(&self, x: usize, y: usize) -> (usize, usize) {
+        ((y / TILE_SIZE) * self.tiles_x + x / TILE_SIZE, (y % TILE_SIZE) * TILE_SIZE + x % TILE_SIZE)
+    }
+
+    /// Value of a cell known to be inside the grid.
+    fn value(&self, x: usize, y: usize) -> V {
+        let (tile, cell) = self.tile_index(x, y);
+        self.tiles[tile].as_ref().map_or(self.background, |tile| tile[cell])
+    }
+
+    /// Mutable access to a cell known to be inside the grid, allocating its tile if needed.
+    fn value_mut(&mut self, x: usize, y: usize) -> &mut V {
+        let (tile, cell) = self.tile_index(x, y);
+        let background = self.background;
+        &mut self.tiles[tile].get_or_insert_with(|| Box::new([background; TILE_CELLS]))[cell]
+    }
+
+    /// Writes a cell known to be inside the grid. Writing the background into a missing
+    /// tile does not allocate it.
+    fn write(&mut self, x: usize, y: usize, value: V) {
+        let (tile, _) = self.tile_index(x, y);
+        if value != self.background || self.tiles[tile].is_some() {
+            *self.value_mut(x, y) = value;
+        }
+    }
+
+    /// Value of cell `(x, y)`, if it exists.
+    pub fn get(&self, x: usize, y: usize) -> Option<V> {
+        (x < self.width && y < self.height).then(|| self.value(x, y))
+    }
+
+    /// Writes cell `(x, y)`; fails outside the grid. Writing the background into a missing
+    /// tile does not allocate it.
+    pub fn set(&mut self, x: usize, y: usize, value: V) -> Result<(), OutOfBounds> {
+        let (width, height) = (self.width, self.height);
+        if x >= width || y >= height {
+            return Err(OutOfBounds { x, y, width, height });
+        }
+        self.write(x, y, value);
+        Ok(())
+    }
+
+    /// Number of allocated tiles.
+    pub fn active_tiles(&self) -> usize {
+        self.tiles.iter().filter(|tile| tile.is_some()).count()
+    }
+
+    /// Approximate heap memory used by the cells, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.tiles.len() * std::mem::size_of::<Option<Tile<V>>>() + self.active_tiles() * std::mem::size_of::<[V; TILE_CELLS]>()
+    }
+
+    /// Frees the tiles whose cells are all within `tolerance` of the background in every
+    /// component, e.g. once smoke has faded out.
+    pub fn prune(&mut self, tolerance: V::Scalar) {
+        let background = self.background;
+        for tile in &mut self.tiles {
+            if tile.as_ref().is_some_and(|cells| cells.iter().all(|&cell| V::difference(cell, background) <= tolerance)) {
+                *tile = None;
+            }
+        }
+    }
+
+    pub fn bilinear_interpolation(&self, x: V::Scalar, y: V::Scalar) -> V {
+        let x0 = x.floor().to_isize();
+        let x1 = x0 + 1;
+        let y0 = y.floor().to_isize();
+        let y1 = y0 + 1;
+
+        let clamp = |v: isize, min: isize, max: isize| v.max(min).min(max) as usize;
+
+        let x0 = clamp(x0, 0, self.width as isize - 1);
+        let x1 = clamp(x1, 0, self.width as isize - 1);
+        let y0 = clamp(y0, 0, self.height as isize - 1);
+        let y1 = clamp(y1, 0, self.height as isize - 1);
+
+        let (zero, one) = (<V::Scalar as Real>::ZERO, V::Scalar::ONE);
+        let tx = (x - V::Scalar::from_usize(x0)).clamp(zero, one);
+        let ty = (y - V::Scalar::from_usize(y0)).clamp(zero, one);
+
+        let a = V::lerp(self.value(x0, y0), self.value(x1, y0), tx);
+        let b = V::lerp(self.value(x0, y1), self.value(x1, y1), tx);
+        V::lerp(a, b, ty)
+    }
+
+    /// Interpolated value at a position in meters.
+    pub fn sample(&self, position: [V::Scalar; 2]) -> V {
+        let [x, y] = self.geometry.index(position);
+        self.bilinear_interpolation(x, y)
+    }
+
+    /// Adds `amount` to the cells whose center lies within a circle, falling off linearly to
+    /// the edge, like `Field2D::splat`. The center and radius are in meters, and only the
+    /// tiles under the circle are visited.
+    pub fn splat(&mut self, position: [V::Scalar; 2], radius: V::Scalar, amount: V) {
+        let center = self.geometry.index(position);
+        let spacing = self.geometry.spacing;
+        let range = |axis: usize, size: usize| {
+            let reach = radius / spacing[axis];
+            let low = (center[axis] - reach).floor().to_isize().max(0) as usize;
+            let high = ((center[axis] + reach).floor().to_isize() + 2).max(0) as usize;
+            low..high.min(size)
+        };
+        for cy in range(1, self.height) {
+            for cx in range(0, self.width) {
+                let offset_x = (V::Scalar::from_usize(cx) - center[0]) * spacing[0];
+                let offset_y = (V::Scalar::from_usize(cy) - center[1]) * spacing[1];
+                let distance = (offset_x * offset_x + offset_y * offset_y).sqrt();
+                if distance < radius {
+                    let value = self.value_mut(cx, cy);
+                    *value = value.add_scaled(amount, V::Scalar::ONE - distance / radius);
+                }
+            }
+        }
+    }
+
+    /// Semi-Lagrangian advection by `velocity_field`, like `Field2D::update`. Only the
+    /// tiles within reach of the current content along the flow are computed, and only
+    /// those that end up with content are kept.
+    pub fn update(&self, velocity_field: &SparseVectorField2D<V::Scalar>, delta_time: V::Scalar) -> Self {
+        let [dx, dy] = self.geometry.spacing;
+        let cell_speed = |velocity: [V::Scalar; 2]| (velocity[0] / dx).abs().max((velocity[1] / dy).abs());
+
+        // Farthest a cell can look back, in tiles, so content can spread into empty tiles.
+        let mut max_speed = cell_speed(velocity_field.background);
+        for cells in velocity_field.tiles.iter().flatten() {
+            for &velocity in cells.iter() {
+                max_speed = max_speed.max(cell_speed(velocity));
+            }
+        }
+        let reach = ((max_speed * delta_time.abs()).to_f64() + 1.0) / TILE_SIZE as f64;
+        let reach = reach.ceil() as usize;
+
+        let mut candidates = vec![false; self.tiles.len()];
+        for tile_y in 0..self.tiles_y {
+            for tile_x in 0..self.tiles_x {
+                if self.tiles[tile_y * self.tiles_x + tile_x].is_none() {
+                    continue;
+                }
+                for ny in tile_y.saturating_sub(reach)..(tile_y + reach + 1).min(self.tiles_y) {
+                    for nx in tile_x.saturating_sub(reach)..(tile_x + reach + 1).min(self.tiles_x) {
+                        candidates[ny * self.tiles_x + nx] = true;
+                    }
+                }
+            }
+        }
+
+        let mut result = Self::new(self.width, self.height, self.background).with_geometry(self.geometry);
+        for (index, _) in candidates.iter().enumerate().filter(|(_, &candidate)| candidate) {
+            let (tile_x, tile_y) = (index % self.tiles_x, index / self.tiles_x);
+            let mut cells = [self.background; TILE_CELLS];
+            let mut empty = true;
+            for (cell, value) in cells.iter_mut().enumerate() {
+                let (x, y) = (tile_x * TILE_SIZE + cell % TILE_SIZE, tile_y * TILE_SIZE + cell / TILE_SIZE);
+                if x >= self.width || y >= self.height {
+                    continue;
+                }
+                let velocity = velocity_field.value(x, y);
+                let px = V::Scalar::from_usize(x) - velocity[0] * delta_time / dx;
+                let py = V::Scalar::from_usize(y) - velocity[1] * delta_time / dy;
+                *value = self.bilinear_interpolation(px, py);
+                empty &= *value == self.background;
+            }
+            if !empty {
+                result.tiles[index] = Some(Box::new(cells));
+            }
+        }
+        result
+    }
+}
+
+impl<T: Real> SparseVectorField2D<T> {
+    /// Semi-Lagrangian self-advection, like `VectorField2D::advect`.
+    pub fn advect(&self, delta_time: T) -> Self {
+        self.update(self, delta_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::support::field::{ColorField2D, VectorField2D};
+
+    #[test]
+    fn writes_allocate_only_their_tile() {
+        let mut field = SparseColorField2D::new(20, 20, 0.0);
+        field.set(3, 4, 0.0).unwrap();
+        assert_eq!(field.active_tiles(), 0);
+
+        field.set(17, 9, 2.0).unwrap();
+        assert_eq!(field.active_tiles(), 1);
+        assert_eq!(field.get(17, 9), Some(2.0));
+        assert_eq!(field.get(16, 9), Some(0.0));
+        assert_eq!(field.get(20, 9), None);
+        assert_eq!(field.set(17, 20, 1.0), Err(OutOfBounds { x: 17, y: 20, width: 20, height: 20 }));
+    }
+
+    #[test]
+    fn faded_tiles_are_pruned() {
+        let mut field = SparseVectorField2D::new(16, 16, [1.0, 0.0]);
+        field.set(2, 2, [1.0, 0.5]).unwrap();
+        field.set(12, 12, [1.0, 1e-4]).unwrap();
+        assert_eq!(field.active_tiles(), 2);
+
+        field.prune(1e-3);
+        assert_eq!(field.active_tiles(), 1);
+        assert_eq!(field.get(12, 12), Some([1.0, 0.0]));
+        assert_eq!(field.get(2, 2), Some([1.0, 0.5]));
+    }
+
+    #[test]
+    fn advection_matches_the_dense_fields() {
+        // A vortex in the middle of a still room, stirring a blob of smoke.
+        let size = 40;
+        let mut velocity = VectorField2D::new(size, size, [0.0, 0.0]);
+        velocity.splat([20.0, 20.0], 8.0, [0.6, -0.4]);
+        let mut smoke = ColorField2D::new(size, size, 0.0);
+        smoke.splat([16.0, 18.0], 5.0, 1.0);
+
+        let sparse_velocity = SparseVectorField2D::from_grid(&velocity.field, [0.0, 0.0]);
+        let mut sparse_smoke = SparseColorField2D::from_grid(&smoke.field, 0.0);
+        let mut sparse_advected = sparse_velocity.clone();
+        let mut advected = velocity.clone();
+        for _ in 0..10 {
+            smoke = smoke.update(&velocity, 1.0);
+            sparse_smoke = sparse_smoke.update(&sparse_velocity, 1.0);
+            advected = advected.advect(1.0);
+            sparse_advected = sparse_advected.advect(1.0);
+        }
+
+        assert!(sparse_smoke.active_tiles() < (size / TILE_SIZE).pow(2));
+        assert_eq!(sparse_smoke.to_grid(), smoke.field);
+        assert_eq!(sparse_advected.to_grid(), advected.field);
+    }
+}
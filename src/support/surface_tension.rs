@@ -0,0 +1,83 @@
+use super::field::VectorField2D;
+use super::level_set::LevelSet;
+
+/// Continuum surface force (Brackbill et al.): surface tension is spread over a band of
+/// `interface_width` cells around the interface as the body force `-sigma * kappa * delta(phi) * n`,
+/// with the distance `phi` in meters and the curvature `kappa` in inverse meters.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceTension {
+    pub coefficient: f32,
+    pub interface_width: f32,
+}
+
+impl SurfaceTension {
+    pub fn new(coefficient: f32) -> Self {
+        Self {
+            coefficient,
+            interface_width: 1.5,
+        }
+    }
+
+    /// Smoothed delta function of the signed distance, on cells `cell_size` meters wide.
+    pub fn delta(&self, phi: f32, cell_size: f32) -> f32 {
+        let epsilon = self.interface_width * cell_size;
+        if phi.abs() > epsilon {
+            0.0
+        } else {
+            0.5 / epsilon * (1.0 + (std::f32::consts::PI * phi / epsilon).cos())
+        }
+    }
+
+    pub fn apply(&self, level_set: &LevelSet, velocity_field: &mut VectorField2D, delta_time: f32) {
+        let [dx, dy] = level_set.phi.geometry.spacing;
+        let cell_size = dx.min(dy);
+        for y in 0..level_set.height() {
+            for x in 0..level_set.width() {
+                let delta = self.delta(level_set.phi.field[y][x], cell_size);
+                if delta == 0.0 {
+                    continue;
+                }
+
+                // Curvature is clamped to what the grid can resolve, a radius of one cell,
+                // which keeps thin filaments and kinks from producing huge spurious forces.
+                let curvature = level_set.curvature(x, y).clamp(-1.0 / cell_size, 1.0 / cell_size);
+                let normal = level_set.normal(x, y);
+                let magnitude = -self.coefficient * curvature * delta * delta_time;
+
+                velocity_field.field[y][x][0] += magnitude * normal[0];
+                velocity_field.field[y][x][1] += magnitude * normal[1];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_integrates_to_one_on_any_cell_size() {
+        let tension = SurfaceTension::new(1.0);
+        for cell_size in [1.0, 0.2] {
+            let steps = 1000;
+            let step = 4.0 * cell_size / steps as f32;
+            let integral: f32 = (0..steps).map(|i| tension.delta(-2.0 * cell_size + (i as f32 + 0.5) * step, cell_size) * step).sum();
+            assert!((integral - 1.0).abs() < 1e-3, "integral {integral} on {cell_size} m cells");
+        }
+    }
+
+    #[test]
+    fn pulls_a_drop_inwards() {
+        let mut level_set = LevelSet::new(32, 32);
+        level_set.add_circle([16.0, 16.0], 8.0);
+        level_set.reinitialize(20);
+        let mut velocity_field = VectorField2D::new(32, 32, [0.0, 0.0]);
+
+        SurfaceTension::new(1.0).apply(&level_set, &mut velocity_field, 0.1);
+
+        assert!(velocity_field.field[16][24][0] < 0.0);
+        assert!(velocity_field.field[16][8][0] > 0.0);
+        assert!(velocity_field.field[24][16][1] < 0.0);
+        assert_eq!(velocity_field.field[0][0], [0.0, 0.0]);
+    }
+}
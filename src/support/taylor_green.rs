@@ -0,0 +1,92 @@
+use super::field::VectorField2D;
+use super::grid::GridGeometry;
+use super::real::Real;
+
+/// Decaying Taylor-Green vortex `u = A sin x cos y e^(-2 nu t)`, `v = -A cos x sin y e^(-2 nu t)`
+/// on the square `[0, pi]^2`, an exact solution of the incompressible Navier-Stokes equations.
+///
+/// The square is covered by `resolution` cells per side inside a ring of edge cells, with the
+/// domain edges half a cell outside the first and last cells. The velocity normal to each edge
+/// and its tangential shear vanish there, so free-slip walls (`EdgeCondition::Wall`) reproduce
+/// the solution exactly. Velocities and viscosity are in domain units, carried to the solver
+/// by the grid geometry. The exact solution and the geometry are computed in double precision
+/// and then rounded to the precision of the fields they are compared with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaylorGreen {
+    pub resolution: usize,
+    /// Peak velocity at `t = 0`, in domain units per unit time.
+    pub amplitude: f64,
+    /// Kinematic viscosity, in domain units.
+    pub viscosity: f64,
+}
+
+impl TaylorGreen {
+    pub fn new(resolution: usize, amplitude: f64, viscosity: f64) -> Self {
+        Self { resolution, amplitude, viscosity }
+    }
+
+    /// Grid cells, including the edge cells, along each side.
+    pub fn size(&self) -> usize {
+        self.resolution + 2
+    }
+
+    /// Width of a cell, in domain units.
+    pub fn cell_size(&self) -> f64 {
+        std::f64::consts::PI / self.resolution as f64
+    }
+
+    /// Placement of the grid, with the domain corner on the edge of the first inner cell.
+    pub fn geometry<T: Real>(&self) -> GridGeometry<T> {
+        let cell_size = self.cell_size();
+        GridGeometry::new([-0.5 * cell_size, -0.5 * cell_size], [cell_size, cell_size]).cast()
+    }
+
+    /// Exact velocity at cell `(x, y)` and time `time`.
+    pub fn velocity(&self, x: usize, y: usize, time: f64) -> [f64; 2] {
+        let [px, py] = self.geometry::<f64>().position(x as f64, y as f64);
+        let speed = self.amplitude * (-2.0 * self.viscosity * time).exp();
+        [speed * px.sin() * py.cos(), -speed * px.cos() * py.sin()]
+    }
+
+    /// The exact velocity field at `time`; `field(0.0)` is the initial condition.
+    pub fn field<T: Real>(&self, time: f64) -> VectorField2D<T> {
+        let size = self.size();
+        let mut velocity_field = VectorField2D::new(size, size, [T::ZERO; 2]).with_geometry(self.geometry());
+        for (y, row) in velocity_field.field.rows_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = self.velocity(x, y, time).map(T::from_f64);
+            }
+        }
+        velocity_field
+    }
+
+    /// Kinetic energy per unit mass averaged over the domain, `A^2 e^(-4 nu t) / 4`.
+    pub fn kinetic_energy(&self, time: f64) -> f64 {
+        0.25 * self.amplitude.powi(2) * (-4.0 * self.viscosity * time).exp()
+    }
+
+    /// Root mean square difference between `velocity_field` and the exact solution over the
+    /// inner cells.
+    pub fn error<T: Real>(&self, velocity_field: &VectorField2D<T>, time: f64) -> f64 {
+        let mut sum = 0.0;
+        for y in 1..=self.resolution {
+            for x in 1..=self.resolution {
+                let exact = self.velocity(x, y, time);
+                let simulated = velocity_field.field[y][x].map(T::to_f64);
+                sum += (simulated[0] - exact[0]).powi(2) + (simulated[1] - exact[1]).powi(2);
+            }
+        }
+        (sum / (self.resolution * self.resolution) as f64).sqrt()
+    }
+
+    /// Mean kinetic energy per unit mass of `velocity_field` over the inner cells.
+    pub fn measured_kinetic_energy<T: Real>(&self, velocity_field: &VectorField2D<T>) -> f64 {
+        let mut sum = 0.0;
+        for y in 1..=self.resolution {
+            for velocity in &velocity_field.field[y][1..=self.resolution] {
+                sum += 0.5 * (velocity[0].to_f64().powi(2) + velocity[1].to_f64().powi(2));
+            }
+        }
+        sum / (self.resolution * self.resolution) as f64
+    }
+}
@@ -0,0 +1,186 @@
+use super::field::{CellValue, ColorField2D, VectorField2D};
+use super::grid::Grid2;
+use super::real::Real;
+use super::viscosity::{shear_rate, velocity_gradient};
+
+/// Largest `Cs^2` the dynamic procedure may pick, so noisy estimates cannot swamp the flow.
+const MAX_DYNAMIC_COEFFICIENT: f32 = 0.1;
+/// Ratio between the test filter and grid filter widths.
+const TEST_FILTER_RATIO: f32 = 2.0;
+
+/// Smagorinsky subgrid model for Large Eddy Simulation: eddies smaller than a cell drain
+/// energy from the resolved flow like an extra viscosity `(Cs * delta)^2 |S|`, where `delta`
+/// is the mean cell size and `|S|` the local shear rate. Without it, energy piles up at the grid
+/// scale at high Reynolds numbers.
+///
+/// The dynamic variant (Germano et al. 1991, with Lilly's least squares) measures `Cs^2`
+/// everywhere from the resolved scales instead, by comparing the flow filtered at the grid
+/// and at twice the grid size. It switches itself off in laminar regions and near walls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Smagorinsky<T = f32> {
+    /// Smagorinsky constant `Cs`, used when the coefficient is not dynamic.
+    pub coefficient: T,
+    pub dynamic: bool,
+}
+
+impl<T: Real> Smagorinsky<T> {
+    pub fn new(coefficient: T) -> Self {
+        Self { coefficient, dynamic: false }
+    }
+
+    pub fn dynamic() -> Self {
+        Self { coefficient: T::from_f32(0.17), dynamic: true }
+    }
+
+    /// The same model in another precision.
+    pub fn cast<U: Real>(&self) -> Smagorinsky<U> {
+        Smagorinsky { coefficient: U::from_f64(self.coefficient.to_f64()), dynamic: self.dynamic }
+    }
+
+    /// Evaluates the eddy viscosity of every cell of `velocity_field` into `eddy_viscosity`,
+    /// in square meters per second.
+    pub fn evaluate(&self, velocity_field: &VectorField2D<T>, eddy_viscosity: &mut ColorField2D<T>) {
+        let (width, height) = (velocity_field.width(), velocity_field.height());
+        let filter_width_squared = velocity_field.geometry.cell_area();
+        let (half, two) = (T::from_f32(0.5), T::from_f32(2.0));
+        let strain = |x: usize, y: usize| {
+            let [[dudx, dudy], [dvdx, dvdy]] = velocity_gradient(velocity_field, x, y);
+            [dudx, half * (dudy + dvdx), dvdy]
+        };
+
+        if !self.dynamic {
+            let coefficient = self.coefficient * self.coefficient * filter_width_squared;
+            eddy_viscosity.field.par_rows_mut(|y, row| {
+                for (x, viscosity) in row.iter_mut().enumerate() {
+                    *viscosity = coefficient * shear_rate(velocity_field, x, y);
+                }
+            });
+            return;
+        }
+
+        // Strain rate S (xx, xy, yy), |S| S and u_i u_j at the grid scale.
+        let mut shear = Grid2::new(width, height, T::ZERO);
+        let mut strain_product = Grid2::new(width, height, [T::ZERO; 3]);
+        let mut velocity_product = Grid2::new(width, height, [T::ZERO; 3]);
+        for y in 0..height {
+            for x in 0..width {
+                let s = strain(x, y);
+                let magnitude = shear_rate(velocity_field, x, y);
+                let [u, v] = velocity_field.field[y][x];
+                shear[y][x] = magnitude;
+                strain_product[y][x] = [magnitude * s[0], magnitude * s[1], magnitude * s[2]];
+                velocity_product[y][x] = [u * u, u * v, v * v];
+            }
+        }
+
+        // The same quantities at the test filter scale.
+        let filtered_velocity = VectorField2D {
+            field: test_filter(&velocity_field.field),
+            ..velocity_field.clone()
+        };
+        let filtered_strain_product = test_filter(&strain_product);
+        let filtered_velocity_product = test_filter(&velocity_product);
+
+        // Germano identity, fitted by least squares: L_ij = Cs^2 M_ij, with the Leonard stress
+        // L_ij = filter(u_i u_j) - filter(u_i) filter(u_j) and
+        // M_ij = 2 delta^2 (filter(|S| S_ij) - ratio^2 |filter(S)| filter(S)_ij).
+        let mut numerator = Grid2::new(width, height, T::ZERO);
+        let mut denominator = Grid2::new(width, height, T::ZERO);
+        let ratio_squared = T::from_f32(TEST_FILTER_RATIO * TEST_FILTER_RATIO);
+        for y in 0..height {
+            for x in 0..width {
+                let [[dudx, dudy], [dvdx, dvdy]] = velocity_gradient(&filtered_velocity, x, y);
+                let s = [dudx, half * (dudy + dvdx), dvdy];
+                let magnitude = shear_rate(&filtered_velocity, x, y);
+                let [u, v] = filtered_velocity.field[y][x];
+                let products = [u * u, u * v, v * v];
+
+                let mut leonard = [T::ZERO; 3];
+                let mut model = [T::ZERO; 3];
+                for k in 0..3 {
+                    leonard[k] = filtered_velocity_product[y][x][k] - products[k];
+                    model[k] = two * filter_width_squared * (filtered_strain_product[y][x][k] - ratio_squared * magnitude * s[k]);
+                }
+                // Only the deviatoric part of the stress is modelled; the trace goes into pressure.
+                let trace = half * (leonard[0] + leonard[2]);
+                leonard[0] -= trace;
+                leonard[2] -= trace;
+
+                // The off-diagonal component appears twice in the contraction.
+                numerator[y][x] = leonard[0] * model[0] + two * leonard[1] * model[1] + leonard[2] * model[2];
+                denominator[y][x] = model[0] * model[0] + two * model[1] * model[1] + model[2] * model[2];
+            }
+        }
+
+        // Averaging over neighbours keeps the coefficient from flickering between cells.
+        let numerator = test_filter(&numerator);
+        let denominator = test_filter(&denominator);
+        for y in 0..height {
+            for x in 0..width {
+                let coefficient = if denominator[y][x] > T::from_f32(f32::EPSILON) {
+                    (numerator[y][x] / denominator[y][x]).clamp(T::ZERO, T::from_f32(MAX_DYNAMIC_COEFFICIENT))
+                } else {
+                    T::ZERO
+                };
+                eddy_viscosity.field[y][x] = coefficient * filter_width_squared * shear[y][x];
+            }
+        }
+    }
+}
+
+/// Test filter of twice the cell size: `[1, 2, 1] / 4` along each axis, with the edge
+/// cells repeated outside the grid.
+fn test_filter<V: CellValue>(field: &Grid2<V>) -> Grid2<V> {
+    let (width, height) = (field.width(), field.height());
+    let weights = [0.25, 0.5, 0.25];
+
+    let mut rows = Grid2::new(width, height, V::ZERO);
+    for y in 0..height {
+        for x in 0..width {
+            for (i, weight) in weights.iter().enumerate() {
+                let nx = (x + i).saturating_sub(1).min(width - 1);
+                rows[y][x] = rows[y][x].add_scaled(field[y][nx], V::Scalar::from_f32(*weight));
+            }
+        }
+    }
+
+    let mut filtered = Grid2::new(width, height, V::ZERO);
+    for y in 0..height {
+        for x in 0..width {
+            for (i, weight) in weights.iter().enumerate() {
+                let ny = (y + i).saturating_sub(1).min(height - 1);
+                filtered[y][x] = filtered[y][x].add_scaled(rows[ny][x], V::Scalar::from_f32(*weight));
+            }
+        }
+    }
+    filtered
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::support::grid::GridGeometry;
+
+    #[test]
+    fn uniform_flow_has_no_eddy_viscosity() {
+        let velocity_field = VectorField2D::new(16, 16, [1.5, -0.5]);
+        for model in [Smagorinsky::new(0.17), Smagorinsky::dynamic()] {
+            let mut eddy_viscosity = ColorField2D::new(16, 16, 1.0);
+            model.evaluate(&velocity_field, &mut eddy_viscosity);
+            assert!(eddy_viscosity.field.cells().all(|&viscosity| viscosity == 0.0), "{model:?}");
+        }
+    }
+
+    #[test]
+    fn static_model_scales_with_cell_area_and_shear() {
+        // u = 0.4 y in meters, on cells half a meter wide.
+        let geometry = GridGeometry::uniform(0.5);
+        let velocity_field = VectorField2D { geometry, field: Grid2::from_fn(16, 16, |_, y| [0.4 * 0.5 * y as f32, 0.0]) };
+        let mut eddy_viscosity = ColorField2D::new(16, 16, 0.0).with_geometry(geometry);
+        Smagorinsky::new(0.2).evaluate(&velocity_field, &mut eddy_viscosity);
+
+        let expected = 0.2 * 0.2 * 0.25 * 0.4;
+        assert!(eddy_viscosity.field.cells().all(|&viscosity| (viscosity - expected).abs() < 1e-6));
+    }
+}
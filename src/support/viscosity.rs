@@ -0,0 +1,123 @@
+use super::field::{ColorField2D, VectorField2D};
+use super::real::Real;
+
+/// Viscosity as a function of the local shear rate, with parameters in the precision `T`
+/// of the solver using it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViscosityModel<T = f32> {
+    /// Constant viscosity.
+    Newtonian(T),
+    /// `mu = consistency * shear_rate^(flow_index - 1)`; shear-thinning when `flow_index < 1`.
+    PowerLaw { consistency: T, flow_index: T },
+    /// Rigid below `yield_stress`, then flows with `plastic_viscosity`. Regularized with
+    /// Papanastasiou's exponential so the viscosity stays finite at rest.
+    Bingham { plastic_viscosity: T, yield_stress: T, regularization: T },
+    /// Smooth transition from `zero_shear` to `infinite_shear` viscosity around shear rate `1 / relaxation_time`.
+    Carreau { zero_shear: T, infinite_shear: T, relaxation_time: T, flow_index: T },
+}
+
+/// Upper bound for the shear-dependent models, whose viscosity can diverge at low shear
+/// rates; it keeps the implicit diffusion solve well conditioned.
+pub const MAX_VISCOSITY: f32 = 50.0;
+/// Shear rate below which power-law models are evaluated, to avoid dividing by zero at rest.
+const MIN_SHEAR_RATE: f32 = 1e-4;
+
+impl<T: Real> ViscosityModel<T> {
+    /// The same model with its parameters in another precision.
+    pub fn cast<U: Real>(&self) -> ViscosityModel<U> {
+        let cast = |value: T| U::from_f64(value.to_f64());
+        match *self {
+            ViscosityModel::Newtonian(viscosity) => ViscosityModel::Newtonian(cast(viscosity)),
+            ViscosityModel::PowerLaw { consistency, flow_index } => ViscosityModel::PowerLaw { consistency: cast(consistency), flow_index: cast(flow_index) },
+            ViscosityModel::Bingham { plastic_viscosity, yield_stress, regularization } => ViscosityModel::Bingham {
+                plastic_viscosity: cast(plastic_viscosity),
+                yield_stress: cast(yield_stress),
+                regularization: cast(regularization),
+            },
+            ViscosityModel::Carreau { zero_shear, infinite_shear, relaxation_time, flow_index } => ViscosityModel::Carreau {
+                zero_shear: cast(zero_shear),
+                infinite_shear: cast(infinite_shear),
+                relaxation_time: cast(relaxation_time),
+                flow_index: cast(flow_index),
+            },
+        }
+    }
+
+    pub fn viscosity(&self, shear_rate: T) -> T {
+        let shear_rate = shear_rate.max(T::from_f32(MIN_SHEAR_RATE));
+        let (one, half) = (T::ONE, T::from_f32(0.5));
+        let viscosity = match *self {
+            ViscosityModel::Newtonian(viscosity) => return viscosity,
+            ViscosityModel::PowerLaw { consistency, flow_index } => consistency * shear_rate.powf(flow_index - one),
+            ViscosityModel::Bingham { plastic_viscosity, yield_stress, regularization } => {
+                plastic_viscosity + yield_stress * (one - (-regularization * shear_rate).exp()) / shear_rate
+            }
+            ViscosityModel::Carreau { zero_shear, infinite_shear, relaxation_time, flow_index } => {
+                let thinning = (one + (relaxation_time * shear_rate).powi(2)).powf((flow_index - one) * half);
+                infinite_shear + (zero_shear - infinite_shear) * thinning
+            }
+        };
+        viscosity.clamp(T::ZERO, T::from_f32(MAX_VISCOSITY))
+    }
+
+    /// Evaluates the model at every cell of `velocity_field` into `viscosity_field`.
+    pub fn evaluate(&self, velocity_field: &VectorField2D<T>, viscosity_field: &mut ColorField2D<T>) {
+        viscosity_field.field.par_rows_mut(|y, row| {
+            for (x, viscosity) in row.iter_mut().enumerate() {
+                *viscosity = self.viscosity(shear_rate(velocity_field, x, y));
+            }
+        });
+    }
+}
+
+/// Velocity gradient `[[du/dx, du/dy], [dv/dx, dv/dy]]` by central differences, one-sided at the edges.
+pub fn velocity_gradient<T: Real>(velocity_field: &VectorField2D<T>, x: usize, y: usize) -> [[T; 2]; 2] {
+    let field = &velocity_field.field;
+    let (left, right) = (x.saturating_sub(1), (x + 1).min(velocity_field.width() - 1));
+    let (up, down) = (y.saturating_sub(1), (y + 1).min(velocity_field.height() - 1));
+    let dx = T::from_usize((right - left).max(1)) * velocity_field.geometry.spacing[0];
+    let dy = T::from_usize((down - up).max(1)) * velocity_field.geometry.spacing[1];
+
+    let mut gradient = [[T::ZERO; 2]; 2];
+    for c in 0..2 {
+        gradient[c][0] = (field[y][right][c] - field[y][left][c]) / dx;
+        gradient[c][1] = (field[down][x][c] - field[up][x][c]) / dy;
+    }
+    gradient
+}
+
+/// Shear rate `sqrt(2 S:S)`, where `S` is the strain rate tensor.
+pub fn shear_rate<T: Real>(velocity_field: &VectorField2D<T>, x: usize, y: usize) -> T {
+    let [[dudx, dudy], [dvdx, dvdy]] = velocity_gradient(velocity_field, x, y);
+    (T::from_f32(2.0) * (dudx * dudx + dvdy * dvdy) + (dudy + dvdx).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::support::grid::{Grid2, GridGeometry};
+
+    #[test]
+    fn simple_shear_rate_is_the_velocity_slope() {
+        // u = 0.3 y in meters, on cells half a meter tall.
+        let geometry = GridGeometry::new([0.0, 0.0], [0.5, 0.5]);
+        let field = Grid2::from_fn(8, 8, |_, y| [0.3 * 0.5 * y as f32, 0.0]);
+        let velocity_field = VectorField2D { geometry, field };
+        assert!((shear_rate(&velocity_field, 4, 4) - 0.3).abs() < 1e-6);
+        assert!((shear_rate(&velocity_field, 4, 0) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn thinning_models_get_thinner_with_shear() {
+        let models = [
+            ViscosityModel::PowerLaw { consistency: 0.5, flow_index: 0.5 },
+            ViscosityModel::Bingham { plastic_viscosity: 0.1, yield_stress: 1.0, regularization: 50.0 },
+            ViscosityModel::Carreau { zero_shear: 20.0, infinite_shear: 0.05, relaxation_time: 10.0, flow_index: 0.3 },
+        ];
+        for model in models {
+            assert!(model.viscosity(0.1) > model.viscosity(1.0), "{model:?}");
+            assert!(model.viscosity(1.0) > model.viscosity(10.0), "{model:?}");
+        }
+        assert_eq!(ViscosityModel::Newtonian(0.1).viscosity(0.0), ViscosityModel::Newtonian(0.1).viscosity(10.0));
+    }
+}